@@ -0,0 +1,369 @@
+use crate::config::Config;
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, KeyAlgorithm};
+use jsonwebtoken::{Algorithm, DecodingKey, Header};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Signature algorithms accepted for OAuth tokens.
+pub const OAUTH_ALGORITHMS: &[Algorithm] = &[Algorithm::RS256, Algorithm::ES256, Algorithm::EdDSA];
+
+/// Cached keys are refetched after this long even if every `kid` is known,
+/// so revoked keys stop validating.
+const JWKS_MAX_AGE: Duration = Duration::from_secs(3600);
+
+/// Discovery documents tried under the issuer URL, in order.
+const DISCOVERY_PATHS: &[&str] = &[
+    "/.well-known/openid-configuration",
+    "/.well-known/oauth-authorization-server",
+];
+
+#[derive(Default)]
+struct CachedKeys {
+    /// The issuer and JWKS file the keys came from; a reload that changes
+    /// either discards them.
+    source: (Option<String>, Option<PathBuf>),
+    keys: Vec<Jwk>,
+    fetched_at: Option<Instant>,
+    attempted_at: Option<Instant>,
+    jwks_uri: Option<String>,
+}
+
+/// Verification keys for OAuth mode. `OAUTH_PUBLIC_KEY_PEM` is used as is;
+/// otherwise keys come from the JWKS in `OAUTH_JWKS_FILE` or the one the
+/// issuer's discovery metadata points to. The JWKS is cached and reloaded
+/// when a token names an unknown `kid`, at most every
+/// `OAUTH_JWKS_MIN_REFRESH_SECS`.
+pub struct KeyStore {
+    client: reqwest::Client,
+    /// Only held to read or swap the keys, never across a fetch.
+    cache: std::sync::Mutex<CachedKeys>,
+    /// Held while the JWKS is fetched, so requests that need a refresh at the
+    /// same time share one fetch.
+    refreshing: Mutex<()>,
+}
+
+impl Default for KeyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyStore {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            cache: std::sync::Mutex::new(CachedKeys::default()),
+            refreshing: Mutex::new(()),
+        }
+    }
+
+    fn cache(&self) -> std::sync::MutexGuard<'_, CachedKeys> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The key that should have signed a token with this header.
+    pub async fn decoding_key(
+        &self,
+        config: &Config,
+        header: &Header,
+    ) -> Result<DecodingKey, String> {
+        if let Some(pem) = &config.oauth_public_key_pem {
+            let key = match header.alg {
+                Algorithm::ES256 => DecodingKey::from_ec_pem(pem.as_bytes()),
+                Algorithm::EdDSA => DecodingKey::from_ed_pem(pem.as_bytes()),
+                _ => DecodingKey::from_rsa_pem(pem.as_bytes()),
+            };
+            return key.map_err(|e| format!("Invalid OAUTH_PUBLIC_KEY_PEM: {}", e));
+        }
+
+        let source = (
+            config.oauth_issuer_url.clone(),
+            config.oauth_jwks_file.clone(),
+        );
+        let (due, known) = self.lookup(config, header, &source);
+        if due {
+            // A token whose key is cached does not wait for someone else's
+            // refresh; one without a key waits and then uses its result.
+            let guard = if known {
+                self.refreshing.try_lock().ok()
+            } else {
+                Some(self.refreshing.lock().await)
+            };
+            if let Some(_guard) = guard {
+                self.refresh_if_due(config, header, &source).await?;
+            }
+        }
+
+        let cache = self.cache();
+        let jwk = find_key(&cache.keys, header).ok_or_else(|| match &header.kid {
+            Some(kid) => format!("No {:?} signing key with kid {}", header.alg, kid),
+            None => format!("No {:?} signing key", header.alg),
+        })?;
+        DecodingKey::from_jwk(jwk).map_err(|e| format!("Invalid JWKS key: {}", e))
+    }
+
+    /// Whether the cached keys should be refetched for this token, and
+    /// whether they already hold its key. Discards keys from another source.
+    fn lookup(
+        &self,
+        config: &Config,
+        header: &Header,
+        source: &(Option<String>, Option<PathBuf>),
+    ) -> (bool, bool) {
+        let mut cache = self.cache();
+        if cache.source != *source {
+            *cache = CachedKeys {
+                source: source.clone(),
+                ..CachedKeys::default()
+            };
+        }
+        let expired = cache
+            .fetched_at
+            .is_none_or(|at| at.elapsed() >= JWKS_MAX_AGE);
+        // An unknown kid may mean the issuer rotated its keys.
+        let known = find_key(&cache.keys, header).is_some();
+        let may_refresh = cache
+            .attempted_at
+            .is_none_or(|at| at.elapsed() >= config.oauth_jwks_min_refresh);
+        ((expired || !known) && may_refresh, known)
+    }
+
+    /// Refetches the keys unless another request did while this one waited
+    /// for `refreshing`.
+    async fn refresh_if_due(
+        &self,
+        config: &Config,
+        header: &Header,
+        source: &(Option<String>, Option<PathBuf>),
+    ) -> Result<(), String> {
+        if !self.lookup(config, header, source).0 {
+            return Ok(());
+        }
+        let jwks_uri = {
+            let mut cache = self.cache();
+            cache.attempted_at = Some(Instant::now());
+            cache.jwks_uri.clone()
+        };
+
+        match self.fetch(config, jwks_uri).await {
+            Ok((keys, jwks_uri)) => {
+                let mut cache = self.cache();
+                // A reload may have switched issuers during the fetch.
+                if cache.source == *source {
+                    tracing::info!("Loaded {} OAuth signing keys", keys.len());
+                    cache.keys = keys;
+                    cache.jwks_uri = jwks_uri;
+                    cache.fetched_at = Some(Instant::now());
+                }
+                Ok(())
+            }
+            Err(e) => {
+                if self.cache().keys.is_empty() {
+                    return Err(e);
+                }
+                tracing::warn!("Keeping cached JWKS after failed refresh: {}", e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Fetches the key set, returning it with the JWKS URI it came from.
+    async fn fetch(
+        &self,
+        config: &Config,
+        jwks_uri: Option<String>,
+    ) -> Result<(Vec<Jwk>, Option<String>), String> {
+        match &config.oauth_jwks_file {
+            Some(path) => {
+                let contents = tokio::fs::read_to_string(path).await.map_err(|e| {
+                    format!("Failed to read OAUTH_JWKS_FILE {}: {}", path.display(), e)
+                })?;
+                let document = serde_json::from_str(&contents)
+                    .map_err(|e| format!("Invalid JWKS in {}: {}", path.display(), e))?;
+                Ok((parse_jwks(&document), None))
+            }
+            None => {
+                let uri = match jwks_uri {
+                    Some(uri) => uri,
+                    None => self.discover_jwks_uri(config).await?,
+                };
+                let document = self.get_json(&uri).await?;
+                Ok((parse_jwks(&document), Some(uri)))
+            }
+        }
+    }
+
+    async fn discover_jwks_uri(&self, config: &Config) -> Result<String, String> {
+        let issuer = config
+            .oauth_issuer_url
+            .as_ref()
+            .ok_or_else(|| "OAUTH_ISSUER_URL is required in oauth mode".to_string())?;
+        let mut last_error = String::new();
+        for path in DISCOVERY_PATHS {
+            let url = format!("{}{}", issuer.trim_end_matches('/'), path);
+            match self.get_json(&url).await {
+                Ok(metadata) => {
+                    return metadata
+                        .get("jwks_uri")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string())
+                        .ok_or_else(|| format!("{} has no jwks_uri", url))
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+
+    async fn get_json(&self, url: &str) -> Result<Value, String> {
+        self.client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Failed to fetch {}: {}", url, e))?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid JSON from {}: {}", url, e))
+    }
+}
+
+/// Parses the keys of a JWKS one by one, skipping those this server cannot
+/// use instead of rejecting the whole set.
+fn parse_jwks(document: &Value) -> Vec<Jwk> {
+    document
+        .get("keys")
+        .and_then(|v| v.as_array())
+        .map(|keys| {
+            keys.iter()
+                .filter_map(|key| serde_json::from_value::<Jwk>(key.clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Picks the key matching the token's `kid`, or any key suitable for its
+/// algorithm when the token has no `kid`.
+fn find_key<'a>(keys: &'a [Jwk], header: &Header) -> Option<&'a Jwk> {
+    keys.iter()
+        .filter(|jwk| match &header.kid {
+            Some(kid) => jwk.common.key_id.as_deref() == Some(kid.as_str()),
+            None => true,
+        })
+        .find(|jwk| key_fits(jwk, header.alg))
+}
+
+fn key_fits(jwk: &Jwk, alg: Algorithm) -> bool {
+    let expected = match alg {
+        Algorithm::RS256 => KeyAlgorithm::RS256,
+        Algorithm::ES256 => KeyAlgorithm::ES256,
+        Algorithm::EdDSA => KeyAlgorithm::EdDSA,
+        _ => return false,
+    };
+    if jwk
+        .common
+        .key_algorithm
+        .is_some_and(|declared| declared != expected)
+    {
+        return false;
+    }
+    match (&jwk.algorithm, alg) {
+        (AlgorithmParameters::RSA(_), Algorithm::RS256) => true,
+        (AlgorithmParameters::EllipticCurve(params), Algorithm::ES256) => {
+            params.curve == EllipticCurve::P256
+        }
+        (AlgorithmParameters::OctetKeyPair(params), Algorithm::EdDSA) => {
+            params.curve == EllipticCurve::Ed25519
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn header(alg: Algorithm, kid: Option<&str>) -> Header {
+        let mut header = Header::new(alg);
+        header.kid = kid.map(|k| k.to_string());
+        header
+    }
+
+    #[test]
+    fn keys_are_selected_by_kid_and_algorithm() {
+        let keys = parse_jwks(&json!({"keys": [
+            {"kty": "oct", "kid": "shared", "k": "c2VjcmV0"},
+            {"kty": "RSA", "kid": "rsa", "n": "AQAB", "e": "AQAB"},
+            {"kty": "EC", "kid": "ec", "crv": "P-256", "x": "AA", "y": "AA"},
+            {"kty": "OKP", "kid": "ed", "crv": "Ed25519", "x": "AA", "alg": "EdDSA"},
+            {"kty": "unknown", "kid": "other"}
+        ]}));
+        assert_eq!(keys.len(), 4);
+
+        let kid =
+            |alg, kid| find_key(&keys, &header(alg, kid)).and_then(|k| k.common.key_id.clone());
+        assert_eq!(kid(Algorithm::RS256, Some("rsa")), Some("rsa".to_string()));
+        assert_eq!(kid(Algorithm::ES256, Some("ec")), Some("ec".to_string()));
+        assert_eq!(kid(Algorithm::EdDSA, None), Some("ed".to_string()));
+        // A key is never used with an algorithm of another family.
+        assert_eq!(kid(Algorithm::ES256, Some("rsa")), None);
+        assert_eq!(kid(Algorithm::HS256, Some("shared")), None);
+        assert_eq!(kid(Algorithm::RS256, Some("missing")), None);
+    }
+
+    #[tokio::test]
+    async fn cached_keys_do_not_wait_for_a_refresh() {
+        // An issuer that accepts connections and never answers.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                open.push(stream);
+            }
+        });
+        let config = Config {
+            oauth_issuer_url: Some(issuer.clone()),
+            ..Config::default()
+        };
+
+        let store = std::sync::Arc::new(KeyStore::new());
+        *store.cache() = CachedKeys {
+            source: (Some(issuer), None),
+            keys: parse_jwks(&json!({"keys": [
+                {"kty": "RSA", "kid": "current", "n": "AQAB", "e": "AQAB"}
+            ]})),
+            fetched_at: Some(Instant::now()),
+            attempted_at: None,
+            jwks_uri: None,
+        };
+
+        let rotated = {
+            let (store, config) = (store.clone(), config.clone());
+            tokio::spawn(async move {
+                store
+                    .decoding_key(&config, &header(Algorithm::RS256, Some("rotated")))
+                    .await
+                    .is_ok()
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let current = tokio::time::timeout(
+            Duration::from_secs(1),
+            store.decoding_key(&config, &header(Algorithm::RS256, Some("current"))),
+        )
+        .await;
+        assert!(
+            matches!(current, Ok(Ok(_))),
+            "a cached key should not wait for another token's refresh"
+        );
+        rotated.abort();
+    }
+}
@@ -0,0 +1,322 @@
+use crate::error::{GitMcpError, Result};
+use crate::tools::{self, ToolContext};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+mod jwks;
+
+pub use jwks::{KeyStore, OAUTH_ALGORITHMS};
+
+/// Claim listing the repository roots a token may operate in.
+const REPO_ROOTS_CLAIM: &str = "repo_roots";
+
+/// Access levels a bearer token can grant. `git:admin` implies every other
+/// scope, and any of them implies `git:read`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Inspect repositories.
+    Read,
+    /// Change local repositories: refs, index, working tree and config.
+    Write,
+    /// Update remotes with `git_push`.
+    Push,
+    /// Destructive operations such as hard resets, cleans and force pushes.
+    Admin,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 4] = [Scope::Read, Scope::Write, Scope::Push, Scope::Admin];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::Read => "git:read",
+            Scope::Write => "git:write",
+            Scope::Push => "git:push",
+            Scope::Admin => "git:admin",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "git:read" => Some(Scope::Read),
+            "git:write" => Some(Scope::Write),
+            "git:push" => Some(Scope::Push),
+            "git:admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The caller behind a validated bearer token.
+#[derive(Debug, Clone, Default)]
+pub struct AuthIdentity {
+    pub subject: Option<String>,
+    /// Scopes from the `scope` or `scp` claim. A token with neither has no
+    /// scopes, unless `MCP_AUTH_ALLOW_UNSCOPED_TOKENS` leaves it unrestricted
+    /// (`None`).
+    pub scopes: Option<Vec<Scope>>,
+    /// Directories the caller's repositories must be inside, from the
+    /// `repo_roots` claim. `None` allows any repository the server can reach.
+    pub repo_roots: Option<Vec<PathBuf>>,
+}
+
+impl AuthIdentity {
+    pub fn from_claims(claims: &Map<String, Value>, allow_unscoped: bool) -> Self {
+        let scopes = match claims.get("scope").or_else(|| claims.get("scp")) {
+            Some(claim) => Some(
+                string_list(claim)
+                    .iter()
+                    .flat_map(|s| s.split_whitespace())
+                    .filter_map(Scope::parse)
+                    .collect(),
+            ),
+            None if allow_unscoped => None,
+            None => Some(Vec::new()),
+        };
+        let repo_roots = claims
+            .get(REPO_ROOTS_CLAIM)
+            .map(|claim| string_list(claim).into_iter().map(PathBuf::from).collect());
+
+        Self {
+            subject: claims
+                .get("sub")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            scopes,
+            repo_roots,
+        }
+    }
+
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        let Some(scopes) = &self.scopes else {
+            return true;
+        };
+        scopes.contains(&scope)
+            || scopes.contains(&Scope::Admin)
+            || (scope == Scope::Read && !scopes.is_empty())
+    }
+
+    /// Whether a tool is worth listing for this caller: it has the scope the
+    /// tool needs for at least some of its arguments.
+    pub fn may_use_tool(&self, tool_name: &str) -> bool {
+        if !self.has_scope(Scope::Read) {
+            return false;
+        }
+        match tool_name {
+            "git_push" => self.has_scope(Scope::Push),
+            name => self.has_scope(Scope::Write) || tools::available_in_read_only(name),
+        }
+    }
+
+    /// Checks a tool call against the caller's scopes and repository roots.
+    pub async fn authorize(
+        &self,
+        ctx: &ToolContext,
+        tool_name: &str,
+        arguments: &Value,
+    ) -> Result<()> {
+        let required = tools::required_scope(tool_name, arguments);
+        if !self.has_scope(required) {
+            return Err(GitMcpError::PermissionDenied(format!(
+                "{} with these arguments requires scope {}; the token grants {}",
+                tool_name,
+                required,
+                self.describe_scopes()
+            )));
+        }
+
+        let Some(roots) = &self.repo_roots else {
+            return Ok(());
+        };
+        for path in target_paths(ctx, arguments).await {
+            // `resolve` leaves `..` only in paths it could not resolve at all.
+            let unresolved = path.components().any(|c| c == Component::ParentDir);
+            if unresolved || !roots.iter().any(|root| path.starts_with(resolve(root))) {
+                return Err(GitMcpError::PermissionDenied(format!(
+                    "{} is outside the repository roots granted to the token",
+                    path.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn describe_scopes(&self) -> String {
+        match &self.scopes {
+            Some(scopes) if !scopes.is_empty() => scopes
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => "no git scopes".to_string(),
+        }
+    }
+}
+
+/// Accepts a claim given either as a JSON array of strings or a single string.
+fn string_list(claim: &Value) -> Vec<String> {
+    match claim {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The directories a call would operate in: its repository (the `path`
+/// argument, else the working directory) plus any clone or worktree target.
+async fn target_paths(ctx: &ToolContext, arguments: &Value) -> Vec<PathBuf> {
+    let arg_path = |key: &str| {
+        arguments
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(PathBuf::from)
+    };
+
+    let repo = match arg_path("path") {
+        Some(path) => resolve(&path),
+        None => {
+            let default_dir = ctx
+                .executor
+                .read()
+                .await
+                .get_working_dir()
+                .cloned()
+                .or_else(|| ctx.config.git_base_dir.clone())
+                .or_else(|| std::env::current_dir().ok())
+                .unwrap_or_default();
+            resolve(&default_dir)
+        }
+    };
+    let mut paths = vec![repo.clone()];
+    for key in ["local_path", "worktree_path"] {
+        if let Some(path) = arg_path(key) {
+            paths.push(resolve(&repo.join(path)));
+        }
+    }
+    paths
+}
+
+/// Canonicalizes `path`, tolerating trailing components that do not exist
+/// yet (for example the target of `git_init`). A `..` among them removes the
+/// component before it, as it would once git creates the directories.
+fn resolve(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return missing
+                .iter()
+                .rev()
+                .fold(canonical, |mut acc: PathBuf, part| {
+                    match part {
+                        Component::ParentDir => {
+                            acc.pop();
+                        }
+                        Component::Normal(name) => acc.push(name),
+                        _ => {}
+                    }
+                    acc
+                });
+        }
+        match (existing.parent(), existing.components().next_back()) {
+            (Some(parent), Some(part @ (Component::Normal(_) | Component::ParentDir))) => {
+                missing.push(part);
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn identity(claims: Value) -> AuthIdentity {
+        AuthIdentity::from_claims(claims.as_object().unwrap(), false)
+    }
+
+    #[test]
+    fn scope_claims() {
+        let reviewer = identity(json!({"sub": "r", "scope": "openid git:read"}));
+        assert_eq!(reviewer.scopes, Some(vec![Scope::Read]));
+        assert!(reviewer.has_scope(Scope::Read));
+        assert!(!reviewer.has_scope(Scope::Write));
+
+        let agent = identity(json!({"scp": ["git:write", "git:push"]}));
+        assert!(agent.has_scope(Scope::Read));
+        assert!(agent.has_scope(Scope::Push));
+        assert!(!agent.has_scope(Scope::Admin));
+
+        let admin = identity(json!({"scope": "git:admin"}));
+        assert!(admin.has_scope(Scope::Push));
+
+        let outsider = identity(json!({"scope": "openid profile"}));
+        assert!(!outsider.has_scope(Scope::Read));
+    }
+
+    #[test]
+    fn tokens_without_scope_claims() {
+        let claims = json!({"sub": "old"});
+        let unscoped = identity(claims.clone());
+        assert_eq!(unscoped.scopes, Some(Vec::new()));
+        assert!(!unscoped.has_scope(Scope::Read));
+        assert!(!unscoped.has_scope(Scope::Admin));
+        assert!(!unscoped.may_use_tool("git_status"));
+
+        let legacy = AuthIdentity::from_claims(claims.as_object().unwrap(), true);
+        assert_eq!(legacy.scopes, None);
+        assert!(legacy.has_scope(Scope::Admin));
+    }
+
+    #[test]
+    fn tool_listing_follows_scopes() {
+        let reviewer = identity(json!({"scope": "git:read"}));
+        assert!(reviewer.may_use_tool("git_log"));
+        assert!(reviewer.may_use_tool("git_branch"));
+        assert!(!reviewer.may_use_tool("git_commit"));
+        assert!(!reviewer.may_use_tool("git_push"));
+
+        let writer = identity(json!({"scope": "git:write"}));
+        assert!(writer.may_use_tool("git_commit"));
+        assert!(!writer.may_use_tool("git_push"));
+    }
+
+    #[test]
+    fn resolve_keeps_missing_components() {
+        let dir = std::env::temp_dir().canonicalize().unwrap();
+        assert_eq!(
+            resolve(&dir.join("does-not-exist/repo")),
+            dir.join("does-not-exist").join("repo")
+        );
+    }
+
+    #[test]
+    fn resolve_applies_parent_components_of_missing_paths() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap().join("root");
+        std::fs::create_dir(&root).unwrap();
+        let escaped = resolve(&root.join("does-not-exist/../../../elsewhere/x"));
+        assert_eq!(
+            escaped,
+            root.parent().unwrap().parent().unwrap().join("elsewhere/x")
+        );
+        assert!(!escaped.starts_with(resolve(&root)));
+        assert_eq!(
+            resolve(&root.join("does-not-exist/../repo")),
+            root.join("repo")
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use super::{
+    log_level, positive, positive_secs, tool_timeout, AuthMode, Config, ResponseFormat,
+    ResponseVerbosity, SessionMode, SigningFormat, TransportType,
+};
+use clap::Parser;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Command-line options. Every option overrides the environment variable
+/// named in its help text; options left out keep the environment's value.
+#[derive(Debug, Default, Parser)]
+#[command(
+    name = "git-mcp-server",
+    version,
+    about = env!("CARGO_PKG_DESCRIPTION"),
+    long_about = None,
+    after_help = "Options override the environment variables shown in brackets. \
+                  Boolean options accept --flag or --flag=false, so a value set \
+                  in the environment can be switched off."
+)]
+pub struct Cli {
+    /// Print the effective configuration, with secrets masked, and exit
+    #[arg(long)]
+    pub print_config: bool,
+
+    /// TOML configuration file, overridden by the environment and these
+    /// options [env: MCP_CONFIG_FILE]
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Only operate on repositories inside this directory [env: GIT_BASE_DIR]
+    #[arg(long, value_name = "DIR", help_heading = "Git")]
+    pub base_dir: Option<PathBuf>,
+
+    /// Further directories repositories may be in, comma separated [env: MCP_REPO_ROOTS]
+    #[arg(long, value_name = "DIRS", value_delimiter = ',', help_heading = "Git")]
+    pub repo_roots: Option<Vec<PathBuf>>,
+
+    /// Committer name [env: GIT_USERNAME]
+    #[arg(long, value_name = "NAME", help_heading = "Git")]
+    pub git_username: Option<String>,
+
+    /// Committer email [env: GIT_EMAIL]
+    #[arg(long, value_name = "EMAIL", help_heading = "Git")]
+    pub git_email: Option<String>,
+
+    /// Sign commits, tags and merges [env: GIT_SIGN_COMMITS]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, require_equals = true,
+          default_missing_value = "true", help_heading = "Git")]
+    pub sign_commits: Option<bool>,
+
+    /// Signature format, passed to git as gpg.format [env: GIT_SIGNING_FORMAT]
+    #[arg(long, value_name = "FORMAT", help_heading = "Git")]
+    pub signing_format: Option<SigningFormat>,
+
+    /// Signing key, passed to git as user.signingkey [env: GIT_SIGNING_KEY]
+    #[arg(long, value_name = "KEY", help_heading = "Git")]
+    pub signing_key: Option<String>,
+
+    /// Allowed signers file for verifying SSH signatures [env: GIT_ALLOWED_SIGNERS_FILE]
+    #[arg(long, value_name = "FILE", help_heading = "Git")]
+    pub allowed_signers_file: Option<PathBuf>,
+
+    /// Custom wrap-up instructions file [env: GIT_WRAPUP_INSTRUCTIONS_PATH]
+    #[arg(long, value_name = "FILE", help_heading = "Git")]
+    pub wrapup_instructions_path: Option<PathBuf>,
+
+    /// Default timeout for each git command [env: GIT_COMMAND_TIMEOUT_SECS]
+    #[arg(long, value_name = "SECS", value_parser = positive_secs, help_heading = "Git")]
+    pub command_timeout: Option<Duration>,
+
+    /// Per-tool timeouts, e.g. git_clone=900,git_log=30 [env: GIT_TOOL_TIMEOUTS]
+    #[arg(long, value_name = "TOOL=SECS", value_delimiter = ',',
+          value_parser = tool_timeout, help_heading = "Git")]
+    pub tool_timeouts: Vec<(String, Duration)>,
+
+    /// Hide and refuse tools that modify repositories [env: MCP_READ_ONLY]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, require_equals = true,
+          default_missing_value = "true", help_heading = "Safety")]
+    pub read_only: Option<bool>,
+
+    /// Scan staged changes for secrets before git_commit [env: MCP_SECRET_SCAN]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, require_equals = true,
+          default_missing_value = "true", help_heading = "Safety")]
+    pub secret_scan: Option<bool>,
+
+    /// Tool call policy file (JSON) [env: MCP_POLICY_FILE]
+    #[arg(long, value_name = "FILE", help_heading = "Safety")]
+    pub policy_file: Option<PathBuf>,
+
+    /// Protected branch and tag patterns, comma separated [env: MCP_PROTECTED_REFS]
+    #[arg(
+        long,
+        value_name = "PATTERNS",
+        value_delimiter = ',',
+        help_heading = "Safety"
+    )]
+    pub protected_refs: Option<Vec<String>>,
+
+    /// Extra secret values to mask in output and logs, comma separated [env: MCP_REDACT_VALUES]
+    #[arg(
+        long,
+        value_name = "VALUES",
+        value_delimiter = ',',
+        help_heading = "Safety"
+    )]
+    pub redact_values: Option<Vec<String>>,
+
+    /// Audit log file (JSONL) [env: MCP_AUDIT_LOG]
+    #[arg(long, value_name = "FILE", help_heading = "Safety")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Rotate the audit log past this size, 0 to never rotate [env: MCP_AUDIT_LOG_MAX_BYTES]
+    #[arg(long, value_name = "BYTES", help_heading = "Safety")]
+    pub audit_log_max_bytes: Option<u64>,
+
+    /// Rotated audit logs to keep [env: MCP_AUDIT_LOG_MAX_FILES]
+    #[arg(long, value_name = "N", help_heading = "Safety")]
+    pub audit_log_max_files: Option<usize>,
+
+    /// Log level or tracing filter directive [env: MCP_LOG_LEVEL]
+    #[arg(long, value_name = "LEVEL", value_parser = log_level, help_heading = "Server")]
+    pub log_level: Option<String>,
+
+    /// Transport [env: MCP_TRANSPORT_TYPE]
+    #[arg(long, value_name = "TRANSPORT", help_heading = "Server")]
+    pub transport: Option<TransportType>,
+
+    /// Requests handled concurrently [env: MCP_MAX_CONCURRENT_REQUESTS]
+    #[arg(long, value_name = "N", value_parser = positive, help_heading = "Server")]
+    pub max_concurrent_requests: Option<usize>,
+
+    /// Text format of tools/call results [env: MCP_RESPONSE_FORMAT]
+    #[arg(long, value_name = "FORMAT", help_heading = "Server")]
+    pub response_format: Option<ResponseFormat>,
+
+    /// Detail of tools/call results [env: MCP_RESPONSE_VERBOSITY]
+    #[arg(long, value_name = "LEVEL", help_heading = "Server")]
+    pub response_verbosity: Option<ResponseVerbosity>,
+
+    /// HTTP listen address [env: MCP_HTTP_HOST]
+    #[arg(long, value_name = "HOST", help_heading = "HTTP")]
+    pub http_host: Option<String>,
+
+    /// HTTP listen port [env: MCP_HTTP_PORT]
+    #[arg(long, value_name = "PORT", help_heading = "HTTP")]
+    pub http_port: Option<u16>,
+
+    /// HTTP MCP endpoint path [env: MCP_HTTP_ENDPOINT_PATH]
+    #[arg(long, value_name = "PATH", help_heading = "HTTP")]
+    pub http_endpoint_path: Option<String>,
+
+    /// Allowed Origin headers, comma separated [env: MCP_ALLOWED_ORIGINS]
+    #[arg(
+        long,
+        value_name = "ORIGINS",
+        value_delimiter = ',',
+        help_heading = "HTTP"
+    )]
+    pub allowed_origins: Option<Vec<String>>,
+
+    /// HTTP session mode [env: MCP_SESSION_MODE]
+    #[arg(long, value_name = "MODE", help_heading = "HTTP")]
+    pub session_mode: Option<SessionMode>,
+
+    /// Close HTTP sessions idle this long, 0 to keep them [env: MCP_SESSION_IDLE_TIMEOUT_SECS]
+    #[arg(long, value_name = "SECS", help_heading = "HTTP")]
+    pub session_idle_timeout: Option<u64>,
+
+    /// Maximum number of HTTP sessions [env: MCP_MAX_SESSIONS]
+    #[arg(long, value_name = "N", value_parser = positive, help_heading = "HTTP")]
+    pub max_sessions: Option<usize>,
+
+    /// Directory where HTTP sessions are persisted [env: MCP_STATE_DIR]
+    #[arg(long, value_name = "DIR", help_heading = "HTTP")]
+    pub state_dir: Option<PathBuf>,
+
+    /// HTTP authentication [env: MCP_AUTH_MODE]
+    #[arg(long, value_name = "MODE", help_heading = "Authentication")]
+    pub auth_mode: Option<AuthMode>,
+
+    /// HS256 secret for jwt mode; prefer the environment, command lines are
+    /// visible to other users [env: MCP_AUTH_SECRET_KEY]
+    #[arg(long, value_name = "SECRET", help_heading = "Authentication")]
+    pub auth_secret_key: Option<String>,
+
+    /// Give tokens without a scope or scp claim every scope [env: MCP_AUTH_ALLOW_UNSCOPED_TOKENS]
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, require_equals = true,
+          default_missing_value = "true", help_heading = "Authentication")]
+    pub auth_allow_unscoped_tokens: Option<bool>,
+
+    /// OAuth issuer [env: OAUTH_ISSUER_URL]
+    #[arg(long, value_name = "URL", help_heading = "Authentication")]
+    pub oauth_issuer_url: Option<String>,
+
+    /// OAuth audience [env: OAUTH_AUDIENCE]
+    #[arg(long, value_name = "AUDIENCE", help_heading = "Authentication")]
+    pub oauth_audience: Option<String>,
+
+    /// Static OAuth public key, PEM encoded [env: OAUTH_PUBLIC_KEY_PEM]
+    #[arg(long, value_name = "PEM", help_heading = "Authentication")]
+    pub oauth_public_key_pem: Option<String>,
+
+    /// Local JWKS file [env: OAUTH_JWKS_FILE]
+    #[arg(long, value_name = "FILE", help_heading = "Authentication")]
+    pub oauth_jwks_file: Option<PathBuf>,
+
+    /// Minimum interval between JWKS refetches [env: OAUTH_JWKS_MIN_REFRESH_SECS]
+    #[arg(long, value_name = "SECS", help_heading = "Authentication")]
+    pub oauth_jwks_min_refresh: Option<u64>,
+}
+
+impl Cli {
+    /// Overrides `config` with every option given on the command line.
+    pub fn apply(&self, config: &mut Config) {
+        fn set<T: Clone>(target: &mut T, value: &Option<T>) {
+            if let Some(value) = value {
+                *target = value.clone();
+            }
+        }
+        fn set_some<T: Clone>(target: &mut Option<T>, value: &Option<T>) {
+            if value.is_some() {
+                *target = value.clone();
+            }
+        }
+        let secs = |value: Option<u64>| value.map(Duration::from_secs);
+
+        set_some(&mut config.git_base_dir, &self.base_dir);
+        set(&mut config.repo_roots, &self.repo_roots);
+        set_some(&mut config.git_username, &self.git_username);
+        set_some(&mut config.git_email, &self.git_email);
+        set(&mut config.git_sign_commits, &self.sign_commits);
+        set_some(&mut config.git_signing_format, &self.signing_format);
+        set_some(&mut config.git_signing_key, &self.signing_key);
+        set_some(
+            &mut config.git_allowed_signers_file,
+            &self.allowed_signers_file,
+        );
+        set_some(
+            &mut config.git_wrapup_instructions_path,
+            &self.wrapup_instructions_path,
+        );
+        set(&mut config.git_command_timeout, &self.command_timeout);
+        config
+            .tool_timeouts
+            .extend(self.tool_timeouts.iter().cloned());
+
+        set(&mut config.read_only, &self.read_only);
+        set(&mut config.secret_scan, &self.secret_scan);
+        set_some(&mut config.policy_file, &self.policy_file);
+        set(&mut config.protected_refs, &self.protected_refs);
+        set(&mut config.redact_values, &self.redact_values);
+        set_some(&mut config.audit_log, &self.audit_log);
+        set(&mut config.audit_log_max_bytes, &self.audit_log_max_bytes);
+        set(&mut config.audit_log_max_files, &self.audit_log_max_files);
+
+        set(&mut config.log_level, &self.log_level);
+        set(&mut config.transport_type, &self.transport);
+        set(
+            &mut config.max_concurrent_requests,
+            &self.max_concurrent_requests,
+        );
+        set(&mut config.response_format, &self.response_format);
+        set(&mut config.response_verbosity, &self.response_verbosity);
+
+        set(&mut config.http_host, &self.http_host);
+        set(&mut config.http_port, &self.http_port);
+        set(&mut config.http_endpoint_path, &self.http_endpoint_path);
+        set(&mut config.allowed_origins, &self.allowed_origins);
+        set(&mut config.session_mode, &self.session_mode);
+        set(
+            &mut config.session_idle_timeout,
+            &secs(self.session_idle_timeout),
+        );
+        set(&mut config.max_sessions, &self.max_sessions);
+        set_some(&mut config.state_dir, &self.state_dir);
+
+        set(&mut config.auth_mode, &self.auth_mode);
+        set_some(&mut config.auth_secret_key, &self.auth_secret_key);
+        set(
+            &mut config.auth_allow_unscoped_tokens,
+            &self.auth_allow_unscoped_tokens,
+        );
+        set_some(&mut config.oauth_issuer_url, &self.oauth_issuer_url);
+        set_some(&mut config.oauth_audience, &self.oauth_audience);
+        set_some(&mut config.oauth_public_key_pem, &self.oauth_public_key_pem);
+        set_some(&mut config.oauth_jwks_file, &self.oauth_jwks_file);
+        set(
+            &mut config.oauth_jwks_min_refresh,
+            &secs(self.oauth_jwks_min_refresh),
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use super::{checked_tool_timeout, choice, log_level, Config};
+use crate::error::{GitMcpError, Result};
+use crate::policy::Policy;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Settings read from the TOML configuration file. Keys are the names
+/// `--print-config` shows; relative paths are resolved against the file's
+/// directory.
+///
+/// ```toml
+/// transport_type = "http"
+/// repo_roots = ["/srv/repos", "/home/agent/work"]
+/// protected_refs = ["main", "release/*"]
+///
+/// [tool_timeouts]
+/// git_clone = 900
+///
+/// [policy]
+/// deny_tools = ["git_clean"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct ConfigFile {
+    git_base_dir: Option<PathBuf>,
+    repo_roots: Option<Vec<PathBuf>>,
+    git_username: Option<String>,
+    git_email: Option<String>,
+    git_sign_commits: Option<bool>,
+    git_signing_format: Option<String>,
+    git_signing_key: Option<String>,
+    git_allowed_signers_file: Option<PathBuf>,
+    git_wrapup_instructions_path: Option<PathBuf>,
+    git_command_timeout_secs: Option<u64>,
+    tool_timeouts: Option<HashMap<String, u64>>,
+
+    read_only: Option<bool>,
+    secret_scan: Option<bool>,
+    policy_file: Option<PathBuf>,
+    policy: Option<Policy>,
+    protected_refs: Option<Vec<String>>,
+    redact_values: Option<Vec<String>>,
+    audit_log: Option<PathBuf>,
+    audit_log_max_bytes: Option<u64>,
+    audit_log_max_files: Option<usize>,
+
+    log_level: Option<String>,
+    transport_type: Option<String>,
+    max_concurrent_requests: Option<usize>,
+    response_format: Option<String>,
+    response_verbosity: Option<String>,
+
+    http_host: Option<String>,
+    http_port: Option<u16>,
+    http_endpoint_path: Option<String>,
+    allowed_origins: Option<Vec<String>>,
+    session_mode: Option<String>,
+    session_idle_timeout_secs: Option<u64>,
+    max_sessions: Option<usize>,
+    state_dir: Option<PathBuf>,
+
+    auth_mode: Option<String>,
+    auth_secret_key: Option<String>,
+    auth_allow_unscoped_tokens: Option<bool>,
+    oauth_issuer_url: Option<String>,
+    oauth_audience: Option<String>,
+    oauth_public_key_pem: Option<String>,
+    oauth_jwks_file: Option<PathBuf>,
+    oauth_jwks_min_refresh_secs: Option<u64>,
+}
+
+impl ConfigFile {
+    pub(super) fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            GitMcpError::ConfigError(format!(
+                "Failed to read config file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        toml::from_str(&contents)
+            .map_err(|e| GitMcpError::ConfigError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Overrides `config` with the settings present in the file at `path`.
+    pub(super) fn apply(self, config: &mut Config, path: &Path) -> Result<()> {
+        let invalid = |key: &str, message: String| {
+            GitMcpError::ConfigError(format!("{}: {}: {}", path.display(), key, message))
+        };
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let resolve = |p: PathBuf| dir.join(p);
+        let secs = Duration::from_secs;
+
+        if self.policy.is_some() && self.policy_file.is_some() {
+            return Err(invalid(
+                "policy",
+                "set either policy or policy_file, not both".to_string(),
+            ));
+        }
+        for (key, value) in [
+            ("max_concurrent_requests", self.max_concurrent_requests),
+            ("max_sessions", self.max_sessions),
+        ] {
+            if value == Some(0) {
+                return Err(invalid(
+                    key,
+                    "expected a positive integer, got 0".to_string(),
+                ));
+            }
+        }
+        if self.git_command_timeout_secs == Some(0) {
+            return Err(invalid(
+                "git_command_timeout_secs",
+                "expected a positive number of seconds, got 0".to_string(),
+            ));
+        }
+
+        set_some(&mut config.git_base_dir, self.git_base_dir.map(resolve));
+        set(
+            &mut config.repo_roots,
+            self.repo_roots
+                .map(|roots| roots.into_iter().map(resolve).collect()),
+        );
+        set_some(&mut config.git_username, self.git_username);
+        set_some(&mut config.git_email, self.git_email);
+        set(&mut config.git_sign_commits, self.git_sign_commits);
+        if let Some(value) = self.git_signing_format {
+            config.git_signing_format =
+                Some(choice(&value).map_err(|e| invalid("git_signing_format", e))?);
+        }
+        set_some(&mut config.git_signing_key, self.git_signing_key);
+        set_some(
+            &mut config.git_allowed_signers_file,
+            self.git_allowed_signers_file.map(resolve),
+        );
+        set_some(
+            &mut config.git_wrapup_instructions_path,
+            self.git_wrapup_instructions_path.map(resolve),
+        );
+        set(
+            &mut config.git_command_timeout,
+            self.git_command_timeout_secs.map(secs),
+        );
+        for (tool, timeout) in self.tool_timeouts.unwrap_or_default() {
+            let (tool, timeout) =
+                checked_tool_timeout(&tool, timeout).map_err(|e| invalid("tool_timeouts", e))?;
+            config.tool_timeouts.insert(tool, timeout);
+        }
+
+        set(&mut config.read_only, self.read_only);
+        set(&mut config.secret_scan, self.secret_scan);
+        set_some(&mut config.policy_file, self.policy_file.map(resolve));
+        if let Some(policy) = self.policy {
+            policy
+                .validate()
+                .map_err(|e| invalid("policy", e.to_string()))?;
+            config.policy = Arc::new(policy);
+        }
+        set(&mut config.protected_refs, self.protected_refs);
+        set(&mut config.redact_values, self.redact_values);
+        set_some(&mut config.audit_log, self.audit_log.map(resolve));
+        set(&mut config.audit_log_max_bytes, self.audit_log_max_bytes);
+        set(&mut config.audit_log_max_files, self.audit_log_max_files);
+
+        if let Some(value) = self.log_level {
+            config.log_level = log_level(&value).map_err(|e| invalid("log_level", e))?;
+        }
+        if let Some(value) = self.transport_type {
+            config.transport_type = choice(&value).map_err(|e| invalid("transport_type", e))?;
+        }
+        set(
+            &mut config.max_concurrent_requests,
+            self.max_concurrent_requests,
+        );
+        if let Some(value) = self.response_format {
+            config.response_format = choice(&value).map_err(|e| invalid("response_format", e))?;
+        }
+        if let Some(value) = self.response_verbosity {
+            config.response_verbosity =
+                choice(&value).map_err(|e| invalid("response_verbosity", e))?;
+        }
+
+        set(&mut config.http_host, self.http_host);
+        set(&mut config.http_port, self.http_port);
+        set(&mut config.http_endpoint_path, self.http_endpoint_path);
+        set(&mut config.allowed_origins, self.allowed_origins);
+        if let Some(value) = self.session_mode {
+            config.session_mode = choice(&value).map_err(|e| invalid("session_mode", e))?;
+        }
+        set(
+            &mut config.session_idle_timeout,
+            self.session_idle_timeout_secs.map(secs),
+        );
+        set(&mut config.max_sessions, self.max_sessions);
+        set_some(&mut config.state_dir, self.state_dir.map(resolve));
+
+        if let Some(value) = self.auth_mode {
+            config.auth_mode = choice(&value).map_err(|e| invalid("auth_mode", e))?;
+        }
+        set_some(&mut config.auth_secret_key, self.auth_secret_key);
+        set(
+            &mut config.auth_allow_unscoped_tokens,
+            self.auth_allow_unscoped_tokens,
+        );
+        set_some(&mut config.oauth_issuer_url, self.oauth_issuer_url);
+        set_some(&mut config.oauth_audience, self.oauth_audience);
+        set_some(&mut config.oauth_public_key_pem, self.oauth_public_key_pem);
+        set_some(
+            &mut config.oauth_jwks_file,
+            self.oauth_jwks_file.map(resolve),
+        );
+        set(
+            &mut config.oauth_jwks_min_refresh,
+            self.oauth_jwks_min_refresh_secs.map(secs),
+        );
+        Ok(())
+    }
+}
+
+fn set<T>(target: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *target = value;
+    }
+}
+
+fn set_some<T>(target: &mut Option<T>, value: Option<T>) {
+    if value.is_some() {
+        *target = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TransportType;
+
+    fn parse(toml: &str) -> Result<Config> {
+        let mut config = Config::default();
+        let file: ConfigFile =
+            toml::from_str(toml).map_err(|e| GitMcpError::ConfigError(e.to_string()))?;
+        file.apply(&mut config, Path::new("/etc/git-mcp/config.toml"))?;
+        Ok(config)
+    }
+
+    #[test]
+    fn settings_and_relative_paths() {
+        let config = parse(
+            r#"
+            transport_type = "HTTP"
+            repo_roots = ["repos", "/srv/git"]
+            audit_log = "audit.jsonl"
+            protected_refs = ["main", "release/*"]
+
+            [tool_timeouts]
+            git_clone = 900
+
+            [[policy.rules]]
+            name = "no-force-push"
+            tool = "git_push"
+            when = { force = true }
+            deny = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.transport_type, TransportType::Http);
+        assert_eq!(
+            config.repo_roots,
+            vec![
+                PathBuf::from("/etc/git-mcp/repos"),
+                PathBuf::from("/srv/git")
+            ]
+        );
+        assert_eq!(
+            config.audit_log,
+            Some(PathBuf::from("/etc/git-mcp/audit.jsonl"))
+        );
+        assert_eq!(config.tool_timeout("git_clone"), Duration::from_secs(900));
+        assert!(config
+            .policy
+            .check("git_push", &serde_json::json!({"force": true}))
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        for toml in [
+            r#"transport_type = "htp""#,
+            r#"read_only = "yes""#,
+            "http_port = 70000",
+            "max_sessions = 0",
+            r#"trasnport_type = "http""#,
+            "[policy]\nrules = [{ name = \"r\" }]",
+            "policy_file = \"p.json\"\n[policy]\ndeny_tools = [\"git_clean\"]",
+            "[tool_timeouts]\ngit_comit = 60",
+            "[tool_timeouts]\ngit_log = 0",
+            "git_command_timeout_secs = 0",
+        ] {
+            assert!(
+                matches!(parse(toml), Err(GitMcpError::ConfigError(_))),
+                "{} was accepted",
+                toml
+            );
+        }
+    }
+}
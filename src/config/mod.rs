@@ -1,61 +1,127 @@
+use crate::audit::AuditLog;
+use crate::error::{GitMcpError, Result};
+use crate::policy::Policy;
+use crate::redact::{Redactor, REDACTED};
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::env;
 use std::path::Path;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing_subscriber::filter::Directive;
+
+mod cli;
+mod file;
+mod reload;
+
+pub use cli::Cli;
+use file::ConfigFile;
+pub use reload::LiveConfig;
 
 #[derive(Debug, Clone)]
 pub struct Config {
+    /// The configuration file the settings were read from, if any.
+    pub config_file: Option<PathBuf>,
     pub git_base_dir: Option<PathBuf>,
+    /// Further directories repositories may be in, besides `git_base_dir`.
+    pub repo_roots: Vec<PathBuf>,
     pub git_username: Option<String>,
     pub git_email: Option<String>,
     pub git_sign_commits: bool,
+    pub git_signing_format: Option<SigningFormat>,
+    pub git_signing_key: Option<String>,
+    pub git_allowed_signers_file: Option<PathBuf>,
+    pub read_only: bool,
+    pub secret_scan: bool,
+    pub policy_file: Option<PathBuf>,
+    pub policy: Arc<Policy>,
+    pub protected_refs: Vec<String>,
+    pub audit_log: Option<PathBuf>,
+    pub audit_log_max_bytes: u64,
+    pub audit_log_max_files: usize,
+    pub audit: Option<Arc<AuditLog>>,
     pub git_wrapup_instructions_path: Option<PathBuf>,
+    pub git_command_timeout: Duration,
+    pub tool_timeouts: HashMap<String, Duration>,
     pub log_level: String,
     pub transport_type: TransportType,
 
+    pub max_concurrent_requests: usize,
+
     pub http_host: String,
     pub http_port: u16,
     pub http_endpoint_path: String,
 
     pub session_mode: SessionMode,
+    pub session_idle_timeout: Duration,
+    pub max_sessions: usize,
+    pub state_dir: Option<PathBuf>,
     pub response_format: ResponseFormat,
     pub response_verbosity: ResponseVerbosity,
 
     pub auth_mode: AuthMode,
     pub auth_secret_key: Option<String>,
+    /// Gives tokens without a `scope` or `scp` claim every scope.
+    pub auth_allow_unscoped_tokens: bool,
+    pub redact_values: Vec<String>,
     pub oauth_issuer_url: Option<String>,
     pub oauth_audience: Option<String>,
     pub oauth_public_key_pem: Option<String>,
+    pub oauth_jwks_file: Option<PathBuf>,
+    pub oauth_jwks_min_refresh: Duration,
     pub allowed_origins: Vec<String>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
 pub enum TransportType {
     Stdio,
     Http,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
 pub enum SessionMode {
     Stateless,
     Stateful,
     Auto,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
 pub enum ResponseFormat {
     Json,
     Markdown,
     Auto,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
 pub enum ResponseVerbosity {
     Minimal,
     Standard,
     Full,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// Signature backend passed to git as `gpg.format`.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum SigningFormat {
+    #[value(name = "openpgp", alias = "gpg")]
+    OpenPgp,
+    Ssh,
+    X509,
+}
+
+impl SigningFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SigningFormat::OpenPgp => "openpgp",
+            SigningFormat::Ssh => "ssh",
+            SigningFormat::X509 => "x509",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
 pub enum AuthMode {
     None,
     Jwt,
@@ -63,122 +129,457 @@ pub enum AuthMode {
 }
 
 impl Default for Config {
+    /// Built-in defaults, before the configuration file, environment and
+    /// command line are applied.
     fn default() -> Self {
-        let transport_type = match env::var("MCP_TRANSPORT_TYPE")
-            .unwrap_or_default()
-            .to_lowercase()
-            .as_str()
-        {
-            "http" => TransportType::Http,
-            _ => TransportType::Stdio,
-        };
-
         Self {
-            git_base_dir: env::var("GIT_BASE_DIR").ok().map(PathBuf::from),
-            git_username: env::var("GIT_USERNAME")
-                .or_else(|_| env::var("GIT_AUTHOR_NAME"))
-                .or_else(|_| env::var("GIT_USER"))
-                .ok(),
-            git_email: env::var("GIT_EMAIL")
-                .or_else(|_| env::var("GIT_AUTHOR_EMAIL"))
-                .or_else(|_| env::var("GIT_USER_EMAIL"))
-                .ok(),
-            git_sign_commits: env::var("GIT_SIGN_COMMITS")
-                .unwrap_or_default()
-                .to_lowercase()
-                == "true",
-            git_wrapup_instructions_path: env::var("GIT_WRAPUP_INSTRUCTIONS_PATH")
-                .ok()
-                .map(PathBuf::from),
-            log_level: env::var("MCP_LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
-            transport_type,
-
-            http_host: env::var("MCP_HTTP_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
-            http_port: env::var("MCP_HTTP_PORT")
-                .ok()
-                .and_then(|v| v.parse::<u16>().ok())
-                .unwrap_or(3015),
-            http_endpoint_path: env::var("MCP_HTTP_ENDPOINT_PATH")
-                .unwrap_or_else(|_| "/mcp".to_string()),
-
-            session_mode: match env::var("MCP_SESSION_MODE")
-                .unwrap_or_else(|_| "auto".to_string())
-                .to_lowercase()
-                .as_str()
-            {
-                "stateless" => SessionMode::Stateless,
-                "stateful" => SessionMode::Stateful,
-                _ => SessionMode::Auto,
-            },
-            response_format: match env::var("MCP_RESPONSE_FORMAT")
-                .unwrap_or_else(|_| "json".to_string())
-                .to_lowercase()
-                .as_str()
-            {
-                "markdown" => ResponseFormat::Markdown,
-                "auto" => ResponseFormat::Auto,
-                _ => ResponseFormat::Json,
-            },
-            response_verbosity: match env::var("MCP_RESPONSE_VERBOSITY")
-                .unwrap_or_else(|_| "standard".to_string())
-                .to_lowercase()
-                .as_str()
-            {
-                "minimal" => ResponseVerbosity::Minimal,
-                "full" => ResponseVerbosity::Full,
-                _ => ResponseVerbosity::Standard,
-            },
+            config_file: None,
+            git_base_dir: None,
+            repo_roots: Vec::new(),
+            git_username: None,
+            git_email: None,
+            git_sign_commits: false,
+            git_signing_format: None,
+            git_signing_key: None,
+            git_allowed_signers_file: None,
+            read_only: false,
+            secret_scan: true,
+            policy_file: None,
+            policy: Arc::new(Policy::default()),
+            protected_refs: Vec::new(),
+            audit_log: None,
+            audit_log_max_bytes: 100 * 1024 * 1024,
+            audit_log_max_files: 10,
+            audit: None,
+            git_wrapup_instructions_path: None,
+            git_command_timeout: Duration::from_secs(300),
+            tool_timeouts: HashMap::new(),
+            log_level: "info".to_string(),
+            transport_type: TransportType::Stdio,
 
-            auth_mode: match env::var("MCP_AUTH_MODE")
-                .unwrap_or_else(|_| "none".to_string())
-                .to_lowercase()
-                .as_str()
-            {
-                "jwt" => AuthMode::Jwt,
-                "oauth" => AuthMode::Oauth,
-                _ => AuthMode::None,
-            },
-            auth_secret_key: env::var("MCP_AUTH_SECRET_KEY").ok(),
-            oauth_issuer_url: env::var("OAUTH_ISSUER_URL").ok(),
-            oauth_audience: env::var("OAUTH_AUDIENCE").ok(),
-            oauth_public_key_pem: env::var("OAUTH_PUBLIC_KEY_PEM").ok(),
-            allowed_origins: env::var("MCP_ALLOWED_ORIGINS")
-                .ok()
-                .map(|s| {
-                    s.split(',')
-                        .map(|x| x.trim().to_string())
-                        .filter(|x| !x.is_empty())
-                        .collect::<Vec<String>>()
-                })
-                .unwrap_or_default(),
+            max_concurrent_requests: 16,
+
+            http_host: "127.0.0.1".to_string(),
+            http_port: 3015,
+            http_endpoint_path: "/mcp".to_string(),
+
+            session_mode: SessionMode::Auto,
+            session_idle_timeout: Duration::from_secs(1800),
+            max_sessions: 100,
+            state_dir: None,
+            response_format: ResponseFormat::Json,
+            response_verbosity: ResponseVerbosity::Standard,
+
+            auth_mode: AuthMode::None,
+            auth_secret_key: None,
+            auth_allow_unscoped_tokens: false,
+            redact_values: Vec::new(),
+            oauth_issuer_url: None,
+            oauth_audience: None,
+            oauth_public_key_pem: None,
+            oauth_jwks_file: None,
+            oauth_jwks_min_refresh: Duration::from_secs(30),
+            allowed_origins: Vec::new(),
         }
     }
 }
 
 impl Config {
-    pub fn from_env() -> Self {
-        Self::default()
+    /// Resolves the configuration from, in increasing precedence, the
+    /// built-in defaults, the configuration file (`--config` or
+    /// `MCP_CONFIG_FILE`), the environment and the command line, then loads
+    /// the policy file. Invalid values are reported, not replaced by defaults.
+    pub fn resolve(cli: &Cli) -> Result<Self> {
+        let mut config = Self::default();
+        let file = match &cli.config {
+            Some(path) => Some(path.clone()),
+            None => env_value("MCP_CONFIG_FILE", path)?,
+        };
+        if let Some(path) = file {
+            ConfigFile::read(&path)?.apply(&mut config, &path)?;
+            config.config_file = Some(path);
+        }
+        config.apply_env()?;
+        cli.apply(&mut config);
+
+        if let Some(path) = &config.policy_file {
+            config.policy = Arc::new(Policy::from_file(path)?);
+        }
+        Ok(config)
+    }
+
+    /// Resolves the configuration and opens the audit log.
+    pub fn load(cli: &Cli) -> Result<Self> {
+        let mut config = Self::resolve(cli)?;
+        config.audit = config.open_audit_log()?;
+        Ok(config)
+    }
+
+    fn open_audit_log(&self) -> Result<Option<Arc<AuditLog>>> {
+        self.audit_log
+            .as_ref()
+            .map(|path| {
+                AuditLog::open(path, self.audit_log_max_bytes, self.audit_log_max_files)
+                    .map(Arc::new)
+            })
+            .transpose()
     }
 
-    pub fn validate_path(&self, path: &Path) -> crate::error::Result<PathBuf> {
+    /// Overrides settings with the environment variables that are set to a
+    /// non-empty value.
+    fn apply_env(&mut self) -> Result<()> {
+        set_from_env(&mut self.git_base_dir, "GIT_BASE_DIR", |v| {
+            path(v).map(Some)
+        })?;
+        set_from_env(&mut self.repo_roots, "MCP_REPO_ROOTS", |v| {
+            list(v).map(|roots| roots.into_iter().map(PathBuf::from).collect())
+        })?;
+        // Later names take precedence, so each list ends with the preferred one.
+        for name in ["GIT_USER", "GIT_AUTHOR_NAME", "GIT_USERNAME"] {
+            set_from_env(&mut self.git_username, name, |v| text(v).map(Some))?;
+        }
+        for name in ["GIT_USER_EMAIL", "GIT_AUTHOR_EMAIL", "GIT_EMAIL"] {
+            set_from_env(&mut self.git_email, name, |v| text(v).map(Some))?;
+        }
+        set_from_env(&mut self.git_sign_commits, "GIT_SIGN_COMMITS", boolean)?;
+        set_from_env(&mut self.git_signing_format, "GIT_SIGNING_FORMAT", |v| {
+            choice(v).map(Some)
+        })?;
+        set_from_env(&mut self.git_signing_key, "GIT_SIGNING_KEY", |v| {
+            text(v).map(Some)
+        })?;
+        set_from_env(
+            &mut self.git_allowed_signers_file,
+            "GIT_ALLOWED_SIGNERS_FILE",
+            |v| path(v).map(Some),
+        )?;
+        set_from_env(&mut self.read_only, "MCP_READ_ONLY", boolean)?;
+        set_from_env(&mut self.secret_scan, "MCP_SECRET_SCAN", boolean)?;
+        set_from_env(&mut self.policy_file, "MCP_POLICY_FILE", |v| {
+            path(v).map(Some)
+        })?;
+        set_from_env(&mut self.protected_refs, "MCP_PROTECTED_REFS", list)?;
+        set_from_env(&mut self.audit_log, "MCP_AUDIT_LOG", |v| path(v).map(Some))?;
+        set_from_env(
+            &mut self.audit_log_max_bytes,
+            "MCP_AUDIT_LOG_MAX_BYTES",
+            number,
+        )?;
+        set_from_env(
+            &mut self.audit_log_max_files,
+            "MCP_AUDIT_LOG_MAX_FILES",
+            number,
+        )?;
+        set_from_env(
+            &mut self.git_wrapup_instructions_path,
+            "GIT_WRAPUP_INSTRUCTIONS_PATH",
+            |v| path(v).map(Some),
+        )?;
+        set_from_env(
+            &mut self.git_command_timeout,
+            "GIT_COMMAND_TIMEOUT_SECS",
+            positive_secs,
+        )?;
+        // Per-tool timeouts add to those from the configuration file.
+        if let Some(timeouts) = env_value("GIT_TOOL_TIMEOUTS", |v| {
+            list(v)?
+                .iter()
+                .map(|entry| tool_timeout(entry))
+                .collect::<ParseResult<Vec<_>>>()
+        })? {
+            self.tool_timeouts.extend(timeouts);
+        }
+        set_from_env(&mut self.log_level, "MCP_LOG_LEVEL", log_level)?;
+        set_from_env(&mut self.transport_type, "MCP_TRANSPORT_TYPE", choice)?;
+        set_from_env(
+            &mut self.max_concurrent_requests,
+            "MCP_MAX_CONCURRENT_REQUESTS",
+            positive,
+        )?;
+
+        set_from_env(&mut self.http_host, "MCP_HTTP_HOST", text)?;
+        set_from_env(&mut self.http_port, "MCP_HTTP_PORT", number)?;
+        set_from_env(&mut self.http_endpoint_path, "MCP_HTTP_ENDPOINT_PATH", text)?;
+
+        set_from_env(&mut self.session_mode, "MCP_SESSION_MODE", choice)?;
+        set_from_env(
+            &mut self.session_idle_timeout,
+            "MCP_SESSION_IDLE_TIMEOUT_SECS",
+            secs,
+        )?;
+        set_from_env(&mut self.max_sessions, "MCP_MAX_SESSIONS", positive)?;
+        set_from_env(&mut self.state_dir, "MCP_STATE_DIR", |v| path(v).map(Some))?;
+        set_from_env(&mut self.response_format, "MCP_RESPONSE_FORMAT", choice)?;
+        set_from_env(
+            &mut self.response_verbosity,
+            "MCP_RESPONSE_VERBOSITY",
+            choice,
+        )?;
+
+        set_from_env(&mut self.auth_mode, "MCP_AUTH_MODE", choice)?;
+        set_from_env(&mut self.auth_secret_key, "MCP_AUTH_SECRET_KEY", |v| {
+            text(v).map(Some)
+        })?;
+        set_from_env(
+            &mut self.auth_allow_unscoped_tokens,
+            "MCP_AUTH_ALLOW_UNSCOPED_TOKENS",
+            boolean,
+        )?;
+        set_from_env(&mut self.redact_values, "MCP_REDACT_VALUES", list)?;
+        set_from_env(&mut self.oauth_issuer_url, "OAUTH_ISSUER_URL", |v| {
+            text(v).map(Some)
+        })?;
+        set_from_env(&mut self.oauth_audience, "OAUTH_AUDIENCE", |v| {
+            text(v).map(Some)
+        })?;
+        set_from_env(
+            &mut self.oauth_public_key_pem,
+            "OAUTH_PUBLIC_KEY_PEM",
+            |v| text(v).map(Some),
+        )?;
+        set_from_env(&mut self.oauth_jwks_file, "OAUTH_JWKS_FILE", |v| {
+            path(v).map(Some)
+        })?;
+        set_from_env(
+            &mut self.oauth_jwks_min_refresh,
+            "OAUTH_JWKS_MIN_REFRESH_SECS",
+            secs,
+        )?;
+        set_from_env(&mut self.allowed_origins, "MCP_ALLOWED_ORIGINS", list)?;
+        Ok(())
+    }
+
+    /// Redactor for text leaving the server, masking the configured secrets
+    /// along with URL credentials and known token formats.
+    pub fn redactor(&self) -> Redactor {
+        Redactor::new(
+            self.auth_secret_key
+                .iter()
+                .chain(&self.redact_values)
+                .cloned(),
+        )
+    }
+
+    /// The settings as `--print-config` shows them, with secrets masked.
+    pub fn masked(&self) -> Value {
+        fn name<T: ValueEnum>(value: &T) -> Option<String> {
+            value.to_possible_value().map(|v| v.get_name().to_string())
+        }
+        let mut tool_timeouts: Vec<_> = self.tool_timeouts.iter().collect();
+        tool_timeouts.sort();
+
+        let value = json!({
+            "config_file": self.config_file,
+            "git_base_dir": self.git_base_dir,
+            "repo_roots": self.repo_roots,
+            "git_username": self.git_username,
+            "git_email": self.git_email,
+            "git_sign_commits": self.git_sign_commits,
+            "git_signing_format": self.git_signing_format.as_ref().and_then(name),
+            "git_signing_key": self.git_signing_key,
+            "git_allowed_signers_file": self.git_allowed_signers_file,
+            "git_wrapup_instructions_path": self.git_wrapup_instructions_path,
+            "git_command_timeout_secs": self.git_command_timeout.as_secs(),
+            "tool_timeouts": tool_timeouts
+                .into_iter()
+                .map(|(tool, timeout)| (tool.clone(), json!(timeout.as_secs())))
+                .collect::<serde_json::Map<_, _>>(),
+            "read_only": self.read_only,
+            "secret_scan": self.secret_scan,
+            "policy_file": self.policy_file,
+            "policy": *self.policy,
+            "protected_refs": self.protected_refs,
+            "redact_values": vec![REDACTED; self.redact_values.len()],
+            "audit_log": self.audit_log,
+            "audit_log_max_bytes": self.audit_log_max_bytes,
+            "audit_log_max_files": self.audit_log_max_files,
+            "log_level": self.log_level,
+            "transport_type": name(&self.transport_type),
+            "max_concurrent_requests": self.max_concurrent_requests,
+            "response_format": name(&self.response_format),
+            "response_verbosity": name(&self.response_verbosity),
+            "http_host": self.http_host,
+            "http_port": self.http_port,
+            "http_endpoint_path": self.http_endpoint_path,
+            "allowed_origins": self.allowed_origins,
+            "session_mode": name(&self.session_mode),
+            "session_idle_timeout_secs": self.session_idle_timeout.as_secs(),
+            "max_sessions": self.max_sessions,
+            "state_dir": self.state_dir,
+            "auth_mode": name(&self.auth_mode),
+            "auth_secret_key": self.auth_secret_key.as_ref().map(|_| REDACTED),
+            "auth_allow_unscoped_tokens": self.auth_allow_unscoped_tokens,
+            "oauth_issuer_url": self.oauth_issuer_url,
+            "oauth_audience": self.oauth_audience,
+            "oauth_public_key_pem": self.oauth_public_key_pem,
+            "oauth_jwks_file": self.oauth_jwks_file,
+            "oauth_jwks_min_refresh_secs": self.oauth_jwks_min_refresh.as_secs(),
+        });
+        // Catches credentials in other values too, such as URL user info.
+        self.redactor().redact_value(value)
+    }
+
+    pub fn tool_timeout(&self, tool_name: &str) -> Duration {
+        self.tool_timeouts
+            .get(tool_name)
+            .copied()
+            .unwrap_or(self.git_command_timeout)
+    }
+
+    pub fn validate_path(&self, path: &Path) -> Result<PathBuf> {
         let canonical = path
             .canonicalize()
-            .map_err(|_| crate::error::GitMcpError::InvalidPath(path.display().to_string()))?;
-
-        if let Some(base) = &self.git_base_dir {
-            let base_canonical = base
-                .canonicalize()
-                .map_err(|_| crate::error::GitMcpError::InvalidPath(base.display().to_string()))?;
-
-            if !canonical.starts_with(&base_canonical) {
-                return Err(crate::error::GitMcpError::InvalidPath(format!(
-                    "Path {} is outside allowed directory {}",
-                    path.display(),
-                    base.display()
-                )));
-            }
+            .map_err(|_| GitMcpError::InvalidPath(path.display().to_string()))?;
+
+        let roots: Vec<&PathBuf> = self.git_base_dir.iter().chain(&self.repo_roots).collect();
+        if roots.is_empty() {
+            return Ok(canonical);
+        }
+        // A root that does not exist cannot contain the path.
+        if roots
+            .iter()
+            .filter_map(|root| root.canonicalize().ok())
+            .any(|root| canonical.starts_with(root))
+        {
+            return Ok(canonical);
         }
+        let allowed: Vec<String> = roots.iter().map(|r| r.display().to_string()).collect();
+        Err(GitMcpError::InvalidPath(format!(
+            "Path {} is outside allowed {} {}",
+            path.display(),
+            if allowed.len() == 1 {
+                "directory"
+            } else {
+                "directories"
+            },
+            allowed.join(", ")
+        )))
+    }
+}
 
-        Ok(canonical)
+/// An environment variable's value, treating an empty value as unset.
+fn env_value<T>(name: &str, parse: impl FnOnce(&str) -> ParseResult<T>) -> Result<Option<T>> {
+    match env::var(name) {
+        Ok(value) if !value.is_empty() => parse(&value)
+            .map(Some)
+            .map_err(|e| GitMcpError::ConfigError(format!("{}: {}", name, e))),
+        Ok(_) | Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(_)) => Err(GitMcpError::ConfigError(format!(
+            "{}: not valid UTF-8",
+            name
+        ))),
+    }
+}
+
+fn set_from_env<T>(
+    target: &mut T,
+    name: &str,
+    parse: impl FnOnce(&str) -> ParseResult<T>,
+) -> Result<()> {
+    if let Some(value) = env_value(name, parse)? {
+        *target = value;
+    }
+    Ok(())
+}
+
+/// Parsers shared by the environment, configuration file and command line.
+/// Errors describe the value; callers add the setting's name.
+type ParseResult<T> = std::result::Result<T, String>;
+
+fn text(value: &str) -> ParseResult<String> {
+    Ok(value.to_string())
+}
+
+fn path(value: &str) -> ParseResult<PathBuf> {
+    Ok(PathBuf::from(value))
+}
+
+/// A comma-separated list, ignoring blank entries.
+fn list(value: &str) -> ParseResult<Vec<String>> {
+    Ok(value
+        .split(',')
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty())
+        .collect())
+}
+
+fn boolean(value: &str) -> ParseResult<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(format!("expected true or false, got '{}'", value)),
+    }
+}
+
+fn number<T: FromStr>(value: &str) -> ParseResult<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("expected a non-negative integer, got '{}'", value))
+}
+
+fn positive(value: &str) -> ParseResult<usize> {
+    match number(value)? {
+        0 => Err("expected a positive integer, got '0'".to_string()),
+        n => Ok(n),
+    }
+}
+
+fn secs(value: &str) -> ParseResult<Duration> {
+    number(value).map(Duration::from_secs)
+}
+
+/// Seconds for the git command timeout, where zero would fail every command.
+fn positive_secs(value: &str) -> ParseResult<Duration> {
+    match number(value)? {
+        0 => Err("expected a positive number of seconds, got '0'".to_string()),
+        n => Ok(Duration::from_secs(n)),
+    }
+}
+
+/// One of a setting's possible values, ignoring case.
+fn choice<T: ValueEnum>(value: &str) -> ParseResult<T> {
+    T::from_str(value.trim(), true).map_err(|_| {
+        let expected: Vec<String> = T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect();
+        format!("expected one of {}, got '{}'", expected.join(", "), value)
+    })
+}
+
+/// A level such as `debug`, or any tracing filter directive.
+fn log_level(value: &str) -> ParseResult<String> {
+    value
+        .parse::<Directive>()
+        .map(|_| value.to_string())
+        .map_err(|e| format!("invalid log level '{}': {}", value, e))
+}
+
+/// One `TOOL=SECS` entry of `GIT_TOOL_TIMEOUTS`, e.g. `git_clone=900`.
+fn tool_timeout(entry: &str) -> ParseResult<(String, Duration)> {
+    let (name, value) = entry
+        .split_once('=')
+        .ok_or_else(|| format!("expected TOOL=SECS, got '{}'", entry))?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(format!("missing tool name in '{}'", entry));
+    }
+    checked_tool_timeout(name, number(value)?)
+}
+
+/// A per-tool timeout, rejecting tools that do not exist and zero seconds,
+/// which would fail every call.
+fn checked_tool_timeout(name: &str, secs: u64) -> ParseResult<(String, Duration)> {
+    if !crate::server::is_tool(name) {
+        return Err(format!("unknown tool '{}'", name));
+    }
+    if secs == 0 {
+        return Err(format!(
+            "expected a positive number of seconds for {}, got 0",
+            name
+        ));
     }
+    Ok((name.to_string(), Duration::from_secs(secs)))
 }
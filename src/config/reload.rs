@@ -0,0 +1,210 @@
+use super::{Cli, Config};
+use crate::error::Result;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+
+/// How often the configuration and policy files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The running configuration. A request works with the snapshot that is
+/// current when it starts; a reload publishes a new snapshot for the requests
+/// that start after it.
+#[derive(Clone)]
+pub struct LiveConfig(watch::Receiver<Arc<Config>>);
+
+impl LiveConfig {
+    /// A configuration that is never reloaded.
+    pub fn fixed(config: Config) -> Self {
+        let (_, rx) = watch::channel(Arc::new(config));
+        Self(rx)
+    }
+
+    /// Reloads `config` on SIGHUP and whenever the configuration file or
+    /// policy file it was read from changes. Without either file there is
+    /// nothing to reload, and SIGHUP keeps its default action.
+    pub fn watch(cli: Cli, config: Config) -> Self {
+        let config = Arc::new(config);
+        let (tx, rx) = watch::channel(config.clone());
+        if !watched_files(&config).is_empty() {
+            // Installed before returning, so an early SIGHUP cannot kill the
+            // server.
+            let hangup = Hangup::new();
+            tokio::spawn(reload_loop(cli, tx, hangup));
+        }
+        Self(rx)
+    }
+
+    pub fn current(&self) -> Arc<Config> {
+        self.0.borrow().clone()
+    }
+
+    /// Waits for the next reload. Returns `None` if the configuration can no
+    /// longer change.
+    pub async fn changed(&mut self) -> Option<Arc<Config>> {
+        self.0.changed().await.ok()?;
+        Some(self.0.borrow_and_update().clone())
+    }
+}
+
+async fn reload_loop(cli: Cli, tx: watch::Sender<Arc<Config>>, mut hangup: Hangup) {
+    let mut ticker = tokio::time::interval(POLL_INTERVAL);
+    let mut stamps = file_stamps(&tx.borrow());
+    loop {
+        tokio::select! {
+            _ = hangup.recv() => tracing::info!("SIGHUP received, reloading configuration"),
+            _ = ticker.tick() => {
+                if file_stamps(&tx.borrow()) == stamps {
+                    continue;
+                }
+                tracing::info!("Configuration file changed, reloading");
+            }
+        }
+        let current = tx.borrow().clone();
+        match reload(&cli, &current) {
+            Ok(next) => {
+                tx.send_replace(next);
+                tracing::info!("Configuration reloaded");
+            }
+            Err(e) => tracing::error!("Keeping the running configuration: {}", e),
+        }
+        // Also after a failure, so a broken file is reported once per edit.
+        stamps = file_stamps(&tx.borrow());
+    }
+}
+
+/// Resolves the configuration again as at startup. Settings that only take
+/// effect at startup keep their running values.
+fn reload(cli: &Cli, current: &Config) -> Result<Arc<Config>> {
+    let mut next = Config::resolve(cli)?;
+
+    let mut kept = Vec::new();
+    keep(
+        &mut kept,
+        "transport_type",
+        &mut next.transport_type,
+        &current.transport_type,
+    );
+    keep(
+        &mut kept,
+        "http_host",
+        &mut next.http_host,
+        &current.http_host,
+    );
+    keep(
+        &mut kept,
+        "http_port",
+        &mut next.http_port,
+        &current.http_port,
+    );
+    keep(
+        &mut kept,
+        "http_endpoint_path",
+        &mut next.http_endpoint_path,
+        &current.http_endpoint_path,
+    );
+    keep(
+        &mut kept,
+        "session_mode",
+        &mut next.session_mode,
+        &current.session_mode,
+    );
+    keep(
+        &mut kept,
+        "max_concurrent_requests",
+        &mut next.max_concurrent_requests,
+        &current.max_concurrent_requests,
+    );
+    keep(
+        &mut kept,
+        "state_dir",
+        &mut next.state_dir,
+        &current.state_dir,
+    );
+    keep(
+        &mut kept,
+        "log_level",
+        &mut next.log_level,
+        &current.log_level,
+    );
+    if !kept.is_empty() {
+        tracing::warn!("Restart the server to apply changes to {}", kept.join(", "));
+    }
+
+    // Entries keep going to the open log unless it moved.
+    next.audit = if next.audit_log == current.audit_log
+        && next.audit_log_max_bytes == current.audit_log_max_bytes
+        && next.audit_log_max_files == current.audit_log_max_files
+    {
+        current.audit.clone()
+    } else {
+        next.open_audit_log()?
+    };
+    Ok(Arc::new(next))
+}
+
+fn keep<T: PartialEq + Clone>(
+    kept: &mut Vec<&'static str>,
+    name: &'static str,
+    next: &mut T,
+    current: &T,
+) {
+    if next != current {
+        kept.push(name);
+        *next = current.clone();
+    }
+}
+
+fn watched_files(config: &Config) -> Vec<PathBuf> {
+    config
+        .config_file
+        .iter()
+        .chain(&config.policy_file)
+        .cloned()
+        .collect()
+}
+
+/// Modification time and size of each watched file; `None` while a file is
+/// missing.
+fn file_stamps(config: &Config) -> Vec<Option<(SystemTime, u64)>> {
+    watched_files(config)
+        .iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(path).ok()?;
+            Some((metadata.modified().ok()?, metadata.len()))
+        })
+        .collect()
+}
+
+/// SIGHUP, on platforms that have it.
+struct Hangup(#[cfg(unix)] Option<tokio::signal::unix::Signal>);
+
+impl Hangup {
+    fn new() -> Self {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            match signal(SignalKind::hangup()) {
+                Ok(signal) => Self(Some(signal)),
+                Err(e) => {
+                    tracing::warn!("Cannot reload on SIGHUP: {}", e);
+                    Self(None)
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        Self()
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.0 {
+            if signal.recv().await.is_some() {
+                return;
+            }
+            self.0 = None;
+        }
+        std::future::pending::<()>().await
+    }
+}
@@ -26,6 +26,15 @@ pub enum GitMcpError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Policy violation ({rule}): {message}")]
+    PolicyViolation { rule: String, message: String },
+
+    #[error("Signing failed: {0}")]
+    SigningFailed(String),
+
     #[error("Operation cancelled: {0}")]
     Cancelled(String),
 
@@ -1,9 +1,66 @@
+use super::progress::{parse_progress_line, ProgressSink};
 use crate::config::Config;
 use crate::error::{GitMcpError, Result};
+use serde::Serialize;
+use std::future::Future;
 use std::path::Path;
 use std::path::PathBuf;
-use std::process::Command;
-use std::sync::Arc;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, Command};
+
+tokio::task_local! {
+    static COMMAND_TIMEOUT: Duration;
+    static COMMAND_LOG: CommandLog;
+    static COMMAND_CONFIG: Arc<Config>;
+}
+
+/// Runs `fut` with every git command it spawns using `config`, the
+/// configuration its request started with, instead of the one the executor
+/// was created with. Sessions outlive configuration reloads.
+pub async fn with_command_config<F: Future>(config: Arc<Config>, fut: F) -> F::Output {
+    COMMAND_CONFIG.scope(config, fut).await
+}
+
+/// Runs `fut` with every git command it spawns limited to `timeout`,
+/// overriding the executor's configured default.
+pub async fn with_command_timeout<F: Future>(timeout: Duration, fut: F) -> F::Output {
+    COMMAND_TIMEOUT.scope(timeout, fut).await
+}
+
+/// A git command as it was run, for the audit log.
+#[derive(Debug, Clone, Serialize)]
+pub struct GitInvocation {
+    pub argv: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    /// `None` if git could not be started or was killed on timeout.
+    pub exit_status: Option<i32>,
+}
+
+pub type CommandLog = Arc<Mutex<Vec<GitInvocation>>>;
+
+/// Runs `fut`, appending every git command it spawns to `log`.
+pub async fn with_command_log<F: Future>(log: CommandLog, fut: F) -> F::Output {
+    COMMAND_LOG.scope(log, fut).await
+}
+
+fn log_invocation(cmd: &Command, args: &[&str], exit_status: Option<i32>) {
+    let _ = COMMAND_LOG.try_with(|log| {
+        let invocation = GitInvocation {
+            argv: std::iter::once("git")
+                .chain(args.iter().copied())
+                .map(|a| a.to_string())
+                .collect(),
+            cwd: cmd.as_std().get_current_dir().map(|d| d.to_path_buf()),
+            exit_status,
+        };
+        if let Ok(mut log) = log.lock() {
+            log.push(invocation);
+        }
+    });
+}
 
 pub struct GitExecutor {
     config: Arc<Config>,
@@ -19,11 +76,17 @@ impl GitExecutor {
     }
 
     pub fn set_working_dir(&mut self, path: PathBuf) -> Result<()> {
-        let validated = self.config.validate_path(&path)?;
+        let validated = self.config().validate_path(&path)?;
         self.working_dir = Some(validated);
         Ok(())
     }
 
+    fn config(&self) -> Arc<Config> {
+        COMMAND_CONFIG
+            .try_with(Arc::clone)
+            .unwrap_or_else(|_| self.config.clone())
+    }
+
     pub fn get_working_dir(&self) -> Option<&PathBuf> {
         self.working_dir.as_ref()
     }
@@ -33,6 +96,7 @@ impl GitExecutor {
     }
 
     fn build_command_in_dir(&self, args: &[&str], dir: Option<&Path>) -> Result<Command> {
+        let config = self.config();
         let mut cmd = Command::new("git");
 
         for arg in args {
@@ -40,64 +104,147 @@ impl GitExecutor {
         }
 
         if let Some(dir) = dir {
-            let validated = self.config.validate_path(dir)?;
+            let validated = config.validate_path(dir)?;
             cmd.current_dir(validated);
         } else if let Some(dir) = &self.working_dir {
-            cmd.current_dir(dir);
-        } else if let Some(base) = &self.config.git_base_dir {
+            // Checked again in case a reload has narrowed the allowed directories.
+            cmd.current_dir(config.validate_path(dir)?);
+        } else if let Some(base) = &config.git_base_dir {
             cmd.current_dir(base);
         }
 
-        if let Some(name) = &self.config.git_username {
+        if let Some(name) = &config.git_username {
             cmd.env("GIT_AUTHOR_NAME", name);
             cmd.env("GIT_COMMITTER_NAME", name);
         }
 
-        if let Some(email) = &self.config.git_email {
+        if let Some(email) = &config.git_email {
             cmd.env("GIT_AUTHOR_EMAIL", email);
             cmd.env("GIT_COMMITTER_EMAIL", email);
         }
 
+        // Signing settings go through `GIT_CONFIG_*` so they override the
+        // repository's config without touching it. They are appended after any
+        // the server itself inherited, which would otherwise be dropped.
+        let mut overrides = Vec::new();
+        if let Some(format) = config.git_signing_format {
+            overrides.push(("gpg.format", format.as_str()));
+        }
+        if let Some(key) = &config.git_signing_key {
+            overrides.push(("user.signingkey", key.as_str()));
+        }
+        let allowed_signers = config
+            .git_allowed_signers_file
+            .as_ref()
+            .map(|p| p.to_string_lossy());
+        if let Some(file) = &allowed_signers {
+            overrides.push(("gpg.ssh.allowedSignersFile", file));
+        }
+        if config.git_sign_commits {
+            overrides.push(("commit.gpgsign", "true"));
+        }
+        if !overrides.is_empty() {
+            let inherited = std::env::var("GIT_CONFIG_COUNT")
+                .ok()
+                .and_then(|count| count.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            cmd.env(
+                "GIT_CONFIG_COUNT",
+                (inherited + overrides.len()).to_string(),
+            );
+            for (i, (key, value)) in (inherited..).zip(overrides) {
+                cmd.env(format!("GIT_CONFIG_KEY_{}", i), key);
+                cmd.env(format!("GIT_CONFIG_VALUE_{}", i), value);
+            }
+        }
+
         cmd.env("GIT_TERMINAL_PROMPT", "0");
+        cmd.stdin(Stdio::null());
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+        // Dropping the future (timeout or client cancellation) must not leave git running.
+        cmd.kill_on_drop(true);
+        // git delegates transports to helpers such as git-remote-http; give the
+        // command its own process group so those die with it.
+        #[cfg(unix)]
+        cmd.process_group(0);
 
         Ok(cmd)
     }
 
-    pub fn execute(&self, args: &[&str]) -> Result<GitOutput> {
-        let mut cmd = self.build_command_in_dir(args, None)?;
-
-        let output = cmd
-            .output()
-            .map_err(|e| GitMcpError::GitCommandFailed(format!("Failed to execute git: {}", e)))?;
-
-        let stdout = output_to_string(output.stdout);
-        let stderr = output_to_string(output.stderr);
+    fn command_timeout(&self) -> Duration {
+        COMMAND_TIMEOUT
+            .try_with(|t| *t)
+            .unwrap_or_else(|_| self.config().git_command_timeout)
+    }
 
-        if !output.status.success() {
-            return Err(GitMcpError::GitCommandFailed(format!(
-                "Git command failed with status {}: {}",
-                output.status, stderr
-            )));
+    async fn run(
+        &self,
+        mut cmd: Command,
+        args: &[&str],
+        stdin_data: Option<&str>,
+        progress: Option<&ProgressSink>,
+        checked: bool,
+    ) -> Result<GitOutput> {
+        if stdin_data.is_some() {
+            cmd.stdin(Stdio::piped());
         }
 
-        Ok(GitOutput {
-            stdout,
-            stderr,
-            status: output.status.code().unwrap_or(-1),
-        })
-    }
-
-    pub fn execute_in_dir(&self, dir: &Path, args: &[&str]) -> Result<GitOutput> {
-        let mut cmd = self.build_command_in_dir(args, Some(dir))?;
+        let timeout = self.command_timeout();
+        let timed_out = || {
+            GitMcpError::Cancelled(format!(
+                "git {} timed out after {}s",
+                args.first().copied().unwrap_or_default(),
+                timeout.as_secs()
+            ))
+        };
+        // A zero budget would otherwise race a command that exits at once.
+        if timeout.is_zero() {
+            log_invocation(&cmd, args, None);
+            return Err(timed_out());
+        }
 
-        let output = cmd
-            .output()
-            .map_err(|e| GitMcpError::GitCommandFailed(format!("Failed to execute git: {}", e)))?;
+        let mut child = cmd.spawn().map_err(|e| {
+            log_invocation(&cmd, args, None);
+            GitMcpError::GitCommandFailed(format!("Failed to spawn git: {}", e))
+        })?;
+        let mut group = ProcessGroupGuard::new(child.id());
+
+        let run = async {
+            if let Some(data) = stdin_data {
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(data.as_bytes()).await.map_err(|e| {
+                        GitMcpError::GitCommandFailed(format!("Failed to write stdin: {}", e))
+                    })?;
+                }
+            }
+
+            let output = match progress {
+                Some(progress) => wait_with_progress(child, progress).await,
+                None => child.wait_with_output().await,
+            };
+            output.map_err(|e| {
+                GitMcpError::GitCommandFailed(format!("Failed to wait for git: {}", e))
+            })
+        };
+
+        let output = match tokio::time::timeout(timeout, run).await {
+            Ok(output) => {
+                group.disarm();
+                let output = output.inspect_err(|_| log_invocation(&cmd, args, None))?;
+                log_invocation(&cmd, args, output.status.code());
+                output
+            }
+            Err(_) => {
+                log_invocation(&cmd, args, None);
+                return Err(timed_out());
+            }
+        };
 
         let stdout = output_to_string(output.stdout);
         let stderr = output_to_string(output.stderr);
 
-        if !output.status.success() {
+        if checked && !output.status.success() {
             return Err(GitMcpError::GitCommandFailed(format!(
                 "Git command failed with status {}: {}",
                 output.status, stderr
@@ -111,85 +258,164 @@ impl GitExecutor {
         })
     }
 
-    pub fn execute_with_stdin(&self, args: &[&str], stdin_data: &str) -> Result<GitOutput> {
-        let mut cmd = self.build_command_in_dir(args, None)?;
-        cmd.stdin(std::process::Stdio::piped());
-        cmd.stdout(std::process::Stdio::piped());
-        cmd.stderr(std::process::Stdio::piped());
+    pub async fn execute(&self, args: &[&str]) -> Result<GitOutput> {
+        let cmd = self.build_command_in_dir(args, None)?;
+        self.run(cmd, args, None, None, true).await
+    }
 
-        let mut child = cmd
-            .spawn()
-            .map_err(|e| GitMcpError::GitCommandFailed(format!("Failed to spawn git: {}", e)))?;
+    pub async fn execute_in_dir(&self, dir: &Path, args: &[&str]) -> Result<GitOutput> {
+        let cmd = self.build_command_in_dir(args, Some(dir))?;
+        self.run(cmd, args, None, None, true).await
+    }
 
-        use std::io::Write;
-        if let Some(mut stdin) = child.stdin.take() {
-            stdin.write_all(stdin_data.as_bytes()).map_err(|e| {
-                GitMcpError::GitCommandFailed(format!("Failed to write stdin: {}", e))
-            })?;
-        }
+    pub async fn execute_with_stdin(&self, args: &[&str], stdin_data: &str) -> Result<GitOutput> {
+        let cmd = self.build_command_in_dir(args, None)?;
+        self.run(cmd, args, Some(stdin_data), None, true).await
+    }
 
-        let output = child
-            .wait_with_output()
-            .map_err(|e| GitMcpError::GitCommandFailed(format!("Failed to wait for git: {}", e)))?;
+    pub async fn execute_with_stdin_in_dir(
+        &self,
+        dir: &Path,
+        args: &[&str],
+        stdin_data: &str,
+    ) -> Result<GitOutput> {
+        let cmd = self.build_command_in_dir(args, Some(dir))?;
+        self.run(cmd, args, Some(stdin_data), None, true).await
+    }
 
-        let stdout = output_to_string(output.stdout);
-        let stderr = output_to_string(output.stderr);
+    /// Runs a command whose exit status is part of its answer, such as
+    /// `git verify-tag`: a non-zero exit is returned as output, with its
+    /// status and stderr, rather than as an error.
+    pub async fn execute_unchecked(&self, dir: Option<&Path>, args: &[&str]) -> Result<GitOutput> {
+        let cmd = self.build_command_in_dir(args, dir)?;
+        self.run(cmd, args, None, None, false).await
+    }
 
-        if !output.status.success() {
-            return Err(GitMcpError::GitCommandFailed(format!(
-                "Git command failed with status {}: {}",
-                output.status, stderr
-            )));
+    /// Runs a helper program such as `gpg` with the same timeout and cleanup
+    /// as git commands, returning whether it exited successfully. Its output
+    /// is discarded.
+    pub async fn helper_succeeds(&self, program: &str, args: &[&str]) -> Result<bool> {
+        let mut cmd = Command::new(program);
+        cmd.args(args);
+        cmd.stdin(Stdio::null());
+        cmd.stdout(Stdio::null());
+        cmd.stderr(Stdio::null());
+        cmd.kill_on_drop(true);
+        #[cfg(unix)]
+        cmd.process_group(0);
+
+        let mut child = cmd.spawn().map_err(|e| {
+            GitMcpError::GitCommandFailed(format!("Failed to spawn {}: {}", program, e))
+        })?;
+        let mut group = ProcessGroupGuard::new(child.id());
+
+        let timeout = self.command_timeout();
+        match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(status) => {
+                group.disarm();
+                let status = status.map_err(|e| {
+                    GitMcpError::GitCommandFailed(format!("Failed to wait for {}: {}", program, e))
+                })?;
+                Ok(status.success())
+            }
+            Err(_) => Err(GitMcpError::Cancelled(format!(
+                "{} timed out after {}s",
+                program,
+                timeout.as_secs()
+            ))),
         }
-
-        Ok(GitOutput {
-            stdout,
-            stderr,
-            status: output.status.code().unwrap_or(-1),
-        })
     }
 
-    pub fn execute_with_stdin_in_dir(
+    /// Runs a command that was given `--progress`, reporting each progress line
+    /// from stderr to `progress` as it streams.
+    pub async fn execute_with_progress(
         &self,
-        dir: &Path,
+        dir: Option<&Path>,
         args: &[&str],
-        stdin_data: &str,
+        progress: Option<&ProgressSink>,
     ) -> Result<GitOutput> {
-        let mut cmd = self.build_command_in_dir(args, Some(dir))?;
-        cmd.stdin(std::process::Stdio::piped());
-        cmd.stdout(std::process::Stdio::piped());
-        cmd.stderr(std::process::Stdio::piped());
-
-        let mut child = cmd
-            .spawn()
-            .map_err(|e| GitMcpError::GitCommandFailed(format!("Failed to spawn git: {}", e)))?;
+        let cmd = self.build_command_in_dir(args, dir)?;
+        self.run(cmd, args, None, progress, true).await
+    }
+}
 
-        use std::io::Write;
-        if let Some(mut stdin) = child.stdin.take() {
-            stdin.write_all(stdin_data.as_bytes()).map_err(|e| {
-                GitMcpError::GitCommandFailed(format!("Failed to write stdin: {}", e))
-            })?;
+/// Like `Child::wait_with_output`, but scans stderr for progress lines
+/// (git separates in-place updates with `\r`) while the command runs.
+async fn wait_with_progress(
+    mut child: Child,
+    progress: &ProgressSink,
+) -> std::io::Result<std::process::Output> {
+    let stdout_pipe = child.stdout.take();
+    let stderr_pipe = child.stderr.take();
+
+    let read_stdout = async {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = stdout_pipe {
+            pipe.read_to_end(&mut buf).await?;
         }
+        Ok::<_, std::io::Error>(buf)
+    };
+
+    let read_stderr = async {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = stderr_pipe {
+            let mut chunk = [0u8; 4096];
+            let mut line_start = 0;
+            loop {
+                let n = pipe.read(&mut chunk).await?;
+                if n == 0 {
+                    break;
+                }
+                buf.extend_from_slice(&chunk[..n]);
+                while let Some(len) = buf[line_start..]
+                    .iter()
+                    .position(|b| *b == b'\r' || *b == b'\n')
+                {
+                    let line = String::from_utf8_lossy(&buf[line_start..line_start + len]);
+                    if let Some(update) = parse_progress_line(&line) {
+                        progress(&update);
+                    }
+                    line_start += len + 1;
+                }
+            }
+        }
+        Ok::<_, std::io::Error>(buf)
+    };
+
+    let (stdout, stderr, status) = tokio::try_join!(read_stdout, read_stderr, child.wait())?;
+    Ok(std::process::Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
 
-        let output = child
-            .wait_with_output()
-            .map_err(|e| GitMcpError::GitCommandFailed(format!("Failed to wait for git: {}", e)))?;
+/// Kills the whole process group of a spawned git command when dropped, unless
+/// the command ran to completion.
+struct ProcessGroupGuard {
+    pgid: Option<u32>,
+}
 
-        let stdout = output_to_string(output.stdout);
-        let stderr = output_to_string(output.stderr);
+impl ProcessGroupGuard {
+    fn new(pid: Option<u32>) -> Self {
+        Self { pgid: pid }
+    }
 
-        if !output.status.success() {
-            return Err(GitMcpError::GitCommandFailed(format!(
-                "Git command failed with status {}: {}",
-                output.status, stderr
-            )));
-        }
+    fn disarm(&mut self) {
+        self.pgid = None;
+    }
+}
 
-        Ok(GitOutput {
-            stdout,
-            stderr,
-            status: output.status.code().unwrap_or(-1),
-        })
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.pgid.and_then(|p| i32::try_from(p).ok()) {
+            // SAFETY: kill(2) has no memory-safety preconditions; a stale group id
+            // only yields ESRCH.
+            unsafe {
+                libc::kill(-pgid, libc::SIGKILL);
+            }
+        }
     }
 }
 
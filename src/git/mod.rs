@@ -1,3 +1,13 @@
 mod executor;
+mod progress;
+mod signing;
 
-pub use executor::{GitExecutor, GitOutput};
+pub use executor::{
+    with_command_config, with_command_log, with_command_timeout, CommandLog, GitExecutor,
+    GitInvocation, GitOutput,
+};
+pub use progress::{parse_progress_line, GitProgress, ProgressSink};
+pub use signing::{
+    ensure_signing_key, object_signature, signing_error, verify_commits, verify_tag, SignatureInfo,
+    SignatureVerification,
+};
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+/// One progress update parsed from git's `--progress` stderr output, e.g.
+/// `Receiving objects:  42% (420/1000), 1.20 MiB | 2.00 MiB/s`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitProgress {
+    pub phase: String,
+    pub percent: Option<u32>,
+    pub current: Option<u64>,
+    pub total: Option<u64>,
+    pub line: String,
+}
+
+/// Receives progress updates while a git command is running.
+pub type ProgressSink = Arc<dyn Fn(&GitProgress) + Send + Sync>;
+
+/// The transfer phases git reports progress for. Other stderr lines, such as
+/// hook output relayed as `remote: ...`, are not progress even when they hold
+/// a number after a colon.
+const PROGRESS_PHASES: &[&str] = &[
+    "Counting objects",
+    "Compressing objects",
+    "Receiving objects",
+    "Resolving deltas",
+    "Writing objects",
+];
+
+pub fn parse_progress_line(line: &str) -> Option<GitProgress> {
+    let line = line.trim();
+    let body = line.strip_prefix("remote:").map(str::trim).unwrap_or(line);
+    let (phase, rest) = body.split_once(':')?;
+    let phase = phase.trim();
+    let rest = rest.trim();
+    if !PROGRESS_PHASES.contains(&phase) {
+        return None;
+    }
+
+    let digits_end = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let leading: u64 = rest[..digits_end].parse().ok()?;
+
+    let (percent, current, total) = if rest[digits_end..].starts_with('%') {
+        let counts = rest
+            .split_once('(')
+            .and_then(|(_, r)| r.split_once(')'))
+            .and_then(|(inner, _)| inner.split_once('/'))
+            .and_then(|(cur, tot)| Some((cur.trim().parse().ok()?, tot.trim().parse().ok()?)));
+        (
+            Some(leading.min(100) as u32),
+            counts.map(|(c, _)| c),
+            counts.map(|(_, t)| t),
+        )
+    } else {
+        (None, Some(leading), None)
+    };
+
+    Some(GitProgress {
+        phase: phase.to_string(),
+        percent,
+        current,
+        total,
+        line: body.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transfer_phases() {
+        let update =
+            parse_progress_line("Receiving objects:  42% (420/1000), 1.20 MiB | 2.00 MiB/s")
+                .unwrap();
+        assert_eq!(update.phase, "Receiving objects");
+        assert_eq!(update.percent, Some(42));
+        assert_eq!(update.current, Some(420));
+        assert_eq!(update.total, Some(1000));
+
+        let update = parse_progress_line("remote: Counting objects: 7, done.").unwrap();
+        assert_eq!(update.phase, "Counting objects");
+        assert_eq!(update.current, Some(7));
+        assert_eq!(update.line, "Counting objects: 7, done.");
+    }
+
+    #[test]
+    fn other_lines_are_not_progress() {
+        for line in [
+            "remote: error: 3 files exceed the size limit",
+            "remote: Build: 12 tests passed",
+            "hint: 2 commits ahead",
+            "CONFLICT (content): Merge conflict in a.txt",
+            "Receiving objects",
+        ] {
+            assert_eq!(parse_progress_line(line), None, "{}", line);
+        }
+    }
+}
@@ -0,0 +1,542 @@
+use super::{GitExecutor, GitOutput};
+use crate::error::{GitMcpError, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Armor lines that open each kind of signature git can create.
+const SIGNATURE_ARMORS: &[(&str, &str)] = &[
+    ("-----BEGIN PGP SIGNATURE-----", "openpgp"),
+    ("-----BEGIN SSH SIGNATURE-----", "ssh"),
+    ("-----BEGIN SIGNED MESSAGE-----", "x509"),
+];
+
+/// Whether a commit or tag object carries a signature, read from the
+/// object itself.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SignatureInfo {
+    pub signed: bool,
+    /// `openpgp`, `ssh` or `x509`.
+    pub format: Option<String>,
+    /// The key that made the signature, as git reports it when checking it
+    /// (`%GK`): a key ID or SSH key fingerprint. `None` when git cannot check
+    /// the signature here, for example SSH without an allowed signers file.
+    pub signing_key: Option<String>,
+}
+
+async fn git(executor: &GitExecutor, dir: Option<&Path>, args: &[&str]) -> Result<GitOutput> {
+    match dir {
+        Some(dir) => executor.execute_in_dir(dir, args).await,
+        None => executor.execute(args).await,
+    }
+}
+
+async fn config_value(executor: &GitExecutor, dir: Option<&Path>, key: &str) -> Option<String> {
+    git(executor, dir, &["config", "--get", key])
+        .await
+        .ok()
+        .map(|o| o.stdout.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Checks that the key git is configured to sign with exists, so a missing
+/// key fails before anything is written instead of deep inside git.
+pub async fn ensure_signing_key(executor: &GitExecutor, dir: Option<&Path>) -> Result<()> {
+    let format = config_value(executor, dir, "gpg.format")
+        .await
+        .unwrap_or_else(|| "openpgp".to_string());
+    let key = config_value(executor, dir, "user.signingkey").await;
+
+    match format.as_str() {
+        "ssh" => {
+            let Some(key) = key else {
+                return Err(GitMcpError::SigningFailed(
+                    "SSH signing needs a key: set GIT_SIGNING_KEY to a key file or key::<public key>"
+                        .to_string(),
+                ));
+            };
+            if key.starts_with("key::") || key.starts_with("ssh-") || key.starts_with("ecdsa-") {
+                return Ok(());
+            }
+            let path = expand_home(&key);
+            if path.is_absolute() && !path.exists() {
+                return Err(GitMcpError::SigningFailed(format!(
+                    "SSH signing key {} does not exist",
+                    path.display()
+                )));
+            }
+            Ok(())
+        }
+        "x509" => {
+            let program = config_value(executor, dir, "gpg.x509.program")
+                .await
+                .unwrap_or_else(|| "gpgsm".to_string());
+            check_secret_key(executor, dir, &program, "X.509", key).await
+        }
+        _ => {
+            let program = match config_value(executor, dir, "gpg.openpgp.program").await {
+                Some(program) => program,
+                None => config_value(executor, dir, "gpg.program")
+                    .await
+                    .unwrap_or_else(|| "gpg".to_string()),
+            };
+            check_secret_key(executor, dir, &program, "OpenPGP", key).await
+        }
+    }
+}
+
+/// Asks `gpg`/`gpgsm` for the secret key matching `key`, or the committer's
+/// email when no key is configured, as git would.
+async fn check_secret_key(
+    executor: &GitExecutor,
+    dir: Option<&Path>,
+    program: &str,
+    kind: &str,
+    key: Option<String>,
+) -> Result<()> {
+    let user_id = match key {
+        Some(key) => key,
+        None => {
+            let ident = git(executor, dir, &["var", "GIT_COMMITTER_IDENT"]).await?;
+            let email = ident
+                .stdout
+                .split_once('<')
+                .and_then(|(_, rest)| rest.split_once('>'))
+                .map(|(email, _)| email.to_string());
+            match email {
+                Some(email) if !email.is_empty() => format!("<{}>", email),
+                _ => {
+                    return Err(GitMcpError::SigningFailed(format!(
+                        "no {} signing key configured and no committer email to find one by; set GIT_SIGNING_KEY",
+                        kind
+                    )))
+                }
+            }
+        }
+    };
+
+    let found = executor
+        .helper_succeeds(program, &["--batch", "--list-secret-keys", &user_id])
+        .await
+        .map_err(|e| match e {
+            GitMcpError::GitCommandFailed(message) => GitMcpError::SigningFailed(message),
+            e => e,
+        })?;
+    if !found {
+        return Err(GitMcpError::SigningFailed(format!(
+            "no {} secret key matching {} is available to {}",
+            kind, user_id, program
+        )));
+    }
+    Ok(())
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// What git and its signing programs print when signing fails.
+const SIGNING_FAILURES: &[&str] = &[
+    "failed to sign",
+    "gpg failed",
+    "couldn't sign",
+    "user.signingkey",
+    "gpg.ssh",
+    "ssh-keygen",
+];
+
+/// Reports a failed signing command as a signing error rather than a
+/// generic git failure.
+pub fn signing_error(error: GitMcpError) -> GitMcpError {
+    match error {
+        GitMcpError::GitCommandFailed(message)
+            if SIGNING_FAILURES
+                .iter()
+                .any(|m| message.to_lowercase().contains(m)) =>
+        {
+            GitMcpError::SigningFailed(message)
+        }
+        other => other,
+    }
+}
+
+/// Reads the signature status of a `commit` or annotated `tag` object. A
+/// lightweight tag or unknown object reads as unsigned.
+pub async fn object_signature(
+    executor: &GitExecutor,
+    dir: Option<&Path>,
+    kind: &str,
+    object: &str,
+) -> SignatureInfo {
+    let format = signature_format(executor, dir, kind, object).await;
+    let signing_key = match (&format, kind) {
+        (None, _) => None,
+        (Some(_), "commit") => verify_commits(executor, dir, &[object.to_string()])
+            .await
+            .ok()
+            .and_then(|results| results.into_iter().next())
+            .and_then(|(_, verification)| verification.key),
+        (Some(_), _) => verify_tag(executor, dir, object).await.key,
+    };
+
+    SignatureInfo {
+        signed: format.is_some(),
+        format,
+        signing_key,
+    }
+}
+
+/// The format of the signature a `commit` or `tag` object carries, if any,
+/// read from the object itself.
+async fn signature_format(
+    executor: &GitExecutor,
+    dir: Option<&Path>,
+    kind: &str,
+    object: &str,
+) -> Option<String> {
+    let content = git(executor, dir, &["cat-file", kind, object])
+        .await
+        .map(|o| o.stdout)
+        .unwrap_or_default();
+    // A commit's signature is in its `gpgsig` header; the message may quote
+    // armor lines without being signed.
+    let signed_part = if kind == "commit" {
+        content.split("\n\n").next().unwrap_or_default()
+    } else {
+        content.as_str()
+    };
+    SIGNATURE_ARMORS
+        .iter()
+        .find(|(armor, _)| signed_part.contains(armor))
+        .map(|(_, format)| format.to_string())
+}
+
+/// The outcome of checking a commit or tag signature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SignatureVerification {
+    /// `good`, `bad`, `unknown` (signed, but not by a trusted, current key,
+    /// or not checkable here) or `unsigned`.
+    pub status: String,
+    /// git's `%G?` code: G, B, U, X, Y, R, E or N.
+    pub code: String,
+    /// The signer's identity (`%GS`): the GPG user ID or SSH principal.
+    pub signer: Option<String>,
+    /// The key that made the signature (`%GK`).
+    pub key: Option<String>,
+    /// The signing key's fingerprint (`%GF`).
+    pub fingerprint: Option<String>,
+    /// Trust in the signing key (`%GT`): ultimate, fully, marginal, never or
+    /// undefined.
+    pub trust: Option<String>,
+    /// Why the signature is not good.
+    pub detail: Option<String>,
+}
+
+impl SignatureVerification {
+    fn from_code(code: &str) -> Self {
+        let (status, detail) = match code {
+            "G" => ("good", None),
+            "B" => ("bad", Some("the signature does not match the content")),
+            "U" => (
+                "unknown",
+                Some("good signature from a key that is not trusted"),
+            ),
+            "X" => ("unknown", Some("good signature that has expired")),
+            "Y" => (
+                "unknown",
+                Some("good signature made by a key that has expired"),
+            ),
+            "R" => ("bad", Some("signature made by a revoked key")),
+            "E" => (
+                "unknown",
+                Some("the signature cannot be checked, usually because the key is missing"),
+            ),
+            _ => ("unsigned", None),
+        };
+        Self {
+            status: status.to_string(),
+            code: code.to_string(),
+            signer: None,
+            key: None,
+            fingerprint: None,
+            trust: None,
+            detail: detail.map(str::to_string),
+        }
+    }
+
+    pub fn is_good(&self) -> bool {
+        self.status == "good"
+    }
+}
+
+/// Codes for which git has run the verification, so `%GT` is known. Asking
+/// for `%GT` on other signed commits makes some git versions abort.
+const CHECKED_CODES: &[&str] = &["G", "U", "X", "Y", "R"];
+
+fn non_empty(s: &str) -> Option<String> {
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+/// First line of git's complaint on stderr, without the `error: ` prefix.
+fn first_error(stderr: &str) -> Option<String> {
+    stderr
+        .lines()
+        .map(|l| l.trim().trim_start_matches("error: "))
+        .find(|l| !l.is_empty())
+        .map(str::to_string)
+}
+
+/// Checks the signatures of the given commits, in order. Callers resolve
+/// client-supplied names to hashes first.
+pub async fn verify_commits(
+    executor: &GitExecutor,
+    dir: Option<&Path>,
+    commits: &[String],
+) -> Result<Vec<(String, SignatureVerification)>> {
+    if commits.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut args = vec![
+        "log",
+        "--no-walk=unsorted",
+        "--format=%H%x1f%G?%x1f%GS%x1f%GK%x1f%GF",
+        "--end-of-options",
+    ];
+    args.extend(commits.iter().map(String::as_str));
+    let output = git(executor, dir, &args).await?;
+
+    let mut results: Vec<(String, SignatureVerification)> = output
+        .stdout
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\x1f').collect();
+            let [hash, code, signer, key, fingerprint] = fields[..] else {
+                return None;
+            };
+            let mut verification = SignatureVerification::from_code(code);
+            verification.signer = non_empty(signer);
+            verification.key = non_empty(key);
+            verification.fingerprint = non_empty(fingerprint);
+            Some((hash.to_string(), verification))
+        })
+        .collect();
+
+    let checked: Vec<&str> = results
+        .iter()
+        .filter(|(_, v)| CHECKED_CODES.contains(&v.code.as_str()))
+        .map(|(hash, _)| hash.as_str())
+        .collect();
+    if !checked.is_empty() {
+        let mut args = vec![
+            "log",
+            "--no-walk=unsorted",
+            "--format=%H%x1f%GT",
+            "--end-of-options",
+        ];
+        args.extend(checked);
+        let trust = git(executor, dir, &args).await?;
+        for line in trust.stdout.lines() {
+            if let Some((hash, level)) = line.split_once('\x1f') {
+                if let Some((_, v)) = results.iter_mut().find(|(h, _)| h == hash) {
+                    v.trust = non_empty(level);
+                }
+            }
+        }
+    }
+
+    // git reports a signature it could not check at all (for example an SSH
+    // signature without an allowed signers file) as unsigned, with the reason
+    // only on stderr.
+    if let Some(reason) = first_error(&output.stderr) {
+        for (hash, verification) in results.iter_mut().filter(|(_, v)| v.code == "N") {
+            if signature_format(executor, dir, "commit", hash)
+                .await
+                .is_some()
+            {
+                verification.status = "unknown".to_string();
+                verification.detail = Some(reason.clone());
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Checks an annotated tag's signature with `git verify-tag --raw`. A
+/// lightweight tag reads as unsigned.
+pub async fn verify_tag(
+    executor: &GitExecutor,
+    dir: Option<&Path>,
+    tag: &str,
+) -> SignatureVerification {
+    match executor
+        .execute_unchecked(dir, &["verify-tag", "--raw", "--end-of-options", tag])
+        .await
+    {
+        Ok(output) => parse_verify_tag(output.status == 0, &output.stderr),
+        Err(e) => {
+            let mut v = SignatureVerification::from_code("E");
+            v.detail = Some(e.to_string());
+            v
+        }
+    }
+}
+
+/// Reads `git verify-tag --raw` output: SSH verification messages, or GPG and
+/// gpgsm `[GNUPG:]` status lines.
+fn parse_verify_tag(success: bool, stderr: &str) -> SignatureVerification {
+    if stderr.contains("cannot verify a non-tag object") {
+        let mut v = SignatureVerification::from_code("N");
+        v.detail = Some("lightweight tag".to_string());
+        return v;
+    }
+    if stderr.contains("no signature found") {
+        return SignatureVerification::from_code("N");
+    }
+
+    // `Good "git" signature for <principal> with <ALG> key <fingerprint>`,
+    // without `for <principal>` when the key is not an allowed signer.
+    if let Some(line) = stderr
+        .lines()
+        .find(|l| l.starts_with("Good \"git\" signature"))
+    {
+        let mut v = SignatureVerification::from_code(if success { "G" } else { "U" });
+        v.signer = line
+            .split_once(" for ")
+            .and_then(|(_, rest)| rest.split_once(" with "))
+            .map(|(principal, _)| principal.to_string());
+        v.key = line.rsplit(' ').next().map(str::to_string);
+        v.fingerprint = v.key.clone();
+        // git has no trust levels for SSH keys.
+        return v;
+    }
+
+    let status = |keyword: &str| {
+        stderr.lines().find_map(|l| {
+            let rest = l.trim().strip_prefix("[GNUPG:] ")?.strip_prefix(keyword)?;
+            (rest.is_empty() || rest.starts_with(' ')).then(|| rest.trim())
+        })
+    };
+    let trust = stderr.lines().find_map(|l| {
+        let rest = l.trim().strip_prefix("[GNUPG:] TRUST_")?;
+        rest.split_whitespace().next().map(str::to_lowercase)
+    });
+    let code = if status("GOODSIG").is_some() {
+        match trust.as_deref() {
+            Some("undefined" | "never") => "U",
+            _ => "G",
+        }
+    } else if status("BADSIG").is_some() {
+        "B"
+    } else if status("EXPSIG").is_some() {
+        "X"
+    } else if status("EXPKEYSIG").is_some() {
+        "Y"
+    } else if status("REVKEYSIG").is_some() {
+        "R"
+    } else if stderr.contains("incorrect signature") || stderr.contains("Bad signature") {
+        "B"
+    } else {
+        "E"
+    };
+
+    let mut v = SignatureVerification::from_code(code);
+    let signature = [
+        "GOODSIG",
+        "BADSIG",
+        "EXPSIG",
+        "EXPKEYSIG",
+        "REVKEYSIG",
+        "ERRSIG",
+    ]
+    .iter()
+    .find_map(|keyword| status(keyword));
+    if let Some(args) = signature {
+        let (key, signer) = args.split_once(' ').unwrap_or((args, ""));
+        v.key = non_empty(key);
+        if code != "E" {
+            v.signer = non_empty(signer);
+        }
+    }
+    v.fingerprint = status("VALIDSIG")
+        .and_then(|args| args.split_whitespace().next())
+        .map(str::to_string);
+    v.trust = trust;
+    if code == "E" {
+        let messages: Vec<&str> = stderr
+            .lines()
+            .filter(|l| !l.trim().starts_with("[GNUPG:]"))
+            .collect();
+        v.detail = first_error(&messages.join("\n")).or(v.detail);
+    }
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gpg_status_lines() {
+        let good = "[GNUPG:] NEWSIG\n\
+            [GNUPG:] GOODSIG 4AA2E3D5C1F0B9A7 Release Bot <release@example.com>\n\
+            [GNUPG:] VALIDSIG 0123456789ABCDEF0123456789ABCDEF01234567 2026-10-01 0 4 0 22 8 00 0123456789ABCDEF0123456789ABCDEF01234567\n\
+            [GNUPG:] TRUST_ULTIMATE 0 pgp\n";
+        let v = parse_verify_tag(true, good);
+        assert_eq!(v.status, "good");
+        assert_eq!(v.code, "G");
+        assert_eq!(
+            v.signer.as_deref(),
+            Some("Release Bot <release@example.com>")
+        );
+        assert_eq!(v.key.as_deref(), Some("4AA2E3D5C1F0B9A7"));
+        assert_eq!(
+            v.fingerprint.as_deref(),
+            Some("0123456789ABCDEF0123456789ABCDEF01234567")
+        );
+        assert_eq!(v.trust.as_deref(), Some("ultimate"));
+
+        let untrusted = good.replace("TRUST_ULTIMATE", "TRUST_UNDEFINED");
+        assert_eq!(parse_verify_tag(true, &untrusted).status, "unknown");
+
+        let bad = "[GNUPG:] BADSIG 4AA2E3D5C1F0B9A7 Release Bot <release@example.com>\n";
+        assert_eq!(parse_verify_tag(false, bad).code, "B");
+
+        let missing = "[GNUPG:] ERRSIG 4AA2E3D5C1F0B9A7 1 8 00 1759276800 9 -\n\
+            [GNUPG:] NO_PUBKEY 4AA2E3D5C1F0B9A7\n\
+            gpg: Can't check signature: No public key\n";
+        let v = parse_verify_tag(false, missing);
+        assert_eq!((v.status.as_str(), v.code.as_str()), ("unknown", "E"));
+        assert_eq!(v.key.as_deref(), Some("4AA2E3D5C1F0B9A7"));
+        assert_eq!(v.signer, None);
+        assert_eq!(
+            v.detail.as_deref(),
+            Some("gpg: Can't check signature: No public key")
+        );
+    }
+
+    #[test]
+    fn ssh_messages() {
+        let v = parse_verify_tag(
+            false,
+            "Good \"git\" signature with ED25519 key SHA256:hn8rRTKeph93\nNo principal matched.\n",
+        );
+        assert_eq!((v.status.as_str(), v.code.as_str()), ("unknown", "U"));
+        assert_eq!(v.signer, None);
+        assert_eq!(v.key.as_deref(), Some("SHA256:hn8rRTKeph93"));
+        assert_eq!(v.trust, None);
+
+        let v = parse_verify_tag(
+            true,
+            "Good \"git\" signature for signer@example.com with ED25519 key SHA256:hn8rRTKeph93\n",
+        );
+        assert_eq!((v.status.as_str(), v.code.as_str()), ("good", "G"));
+        assert_eq!(v.signer.as_deref(), Some("signer@example.com"));
+        assert_eq!(v.trust, None);
+
+        let v = parse_verify_tag(false, "error: no signature found\n");
+        assert_eq!(v.status, "unsigned");
+    }
+}
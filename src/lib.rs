@@ -1,5 +1,13 @@
+// `Config::masked` builds one large `json!` object.
+#![recursion_limit = "256"]
+
+pub mod audit;
+pub mod auth;
 pub mod config;
 pub mod error;
 pub mod git;
+pub mod policy;
+pub mod redact;
 pub mod server;
+pub mod session;
 pub mod tools;
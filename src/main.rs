@@ -1,28 +1,52 @@
+// `Config::masked` builds one large `json!` object.
+#![recursion_limit = "256"]
+
+pub mod audit;
+pub mod auth;
 pub mod config;
 pub mod error;
 pub mod git;
+pub mod policy;
+pub mod redact;
+pub mod session;
 pub mod tools;
 
-use config::Config;
+use clap::Parser;
+use config::{Cli, Config, LiveConfig};
 
 mod server;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    if cli.print_config {
+        let config = Config::resolve(&cli)?;
+        println!("{}", serde_json::to_string_pretty(&config.masked())?);
+        return Ok(());
+    }
+    let config = Config::load(&cli)?;
+
+    let level = config
+        .log_level
+        .parse()
+        .unwrap_or_else(|_| tracing::Level::INFO.into());
+    let log_writer = redact::RedactingStderr::new(config.redactor());
     tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(tracing::Level::INFO.into()),
-        )
-        .with_writer(std::io::stderr)
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env().add_directive(level))
+        .with_writer(log_writer.clone())
         .init();
 
-    let config = Config::from_env();
-
     tracing::info!("Starting Git MCP Server");
     tracing::debug!("Config: {:?}", config);
 
-    server::run_server(config).await?;
+    let live = LiveConfig::watch(cli, config);
+    let mut reloads = live.clone();
+    tokio::spawn(async move {
+        while let Some(config) = reloads.changed().await {
+            log_writer.set_redactor(config.redactor());
+        }
+    });
+    server::run_server(live).await?;
 
     Ok(())
 }
@@ -0,0 +1,564 @@
+use crate::error::{GitMcpError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+mod protected;
+
+pub use protected::check_protected_refs;
+
+/// Declarative restrictions on tool calls, loaded from `MCP_POLICY_FILE` or
+/// the `[policy]` table of the configuration file, and checked in `execute_tool` before a tool runs.
+///
+/// ```json
+/// {
+///   "deny_tools": ["git_clean"],
+///   "rules": [
+///     {"name": "no-force-push", "tool": "git_push", "when": {"force": true}, "deny": true},
+///     {"name": "agent-branches", "tool": "git_branch", "when": {"operation": "delete"},
+///      "require": {"name": "agent/*"}}
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// If set, only tools matching one of these patterns may be called.
+    #[serde(default)]
+    pub allow_tools: Option<Vec<String>>,
+    /// Tools matching any of these patterns may never be called.
+    #[serde(default)]
+    pub deny_tools: Vec<String>,
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+}
+
+/// A rule applies to calls of tools matching `tool` whose arguments match
+/// every condition in `when`. Such calls are refused if `deny` is set, or if
+/// any condition in `require` does not match.
+///
+/// A condition maps an argument name to the expected value. Strings are glob
+/// patterns (`*` and `?`), arrays list alternatives, and anything else must be
+/// equal. An absent argument only matches `null`. A non-empty array argument
+/// matches a `when` condition if any element does, so one harmless path cannot
+/// hide another, and a `require` condition only if every element does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyRule {
+    pub name: String,
+    #[serde(default = "any_tool")]
+    pub tool: String,
+    #[serde(default)]
+    pub when: Map<String, Value>,
+    #[serde(default)]
+    pub require: Map<String, Value>,
+    #[serde(default)]
+    pub deny: bool,
+    /// Explanation returned to the client instead of the generated one.
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+fn any_tool() -> String {
+    "*".to_string()
+}
+
+impl Policy {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            GitMcpError::ConfigError(format!(
+                "Failed to read policy file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::from_json(&contents).map_err(|e| match e {
+            GitMcpError::ConfigError(msg) => {
+                GitMcpError::ConfigError(format!("{}: {}", path.display(), msg))
+            }
+            other => other,
+        })
+    }
+
+    pub fn from_json(contents: &str) -> Result<Self> {
+        let policy: Self = serde_json::from_str(contents)
+            .map_err(|e| GitMcpError::ConfigError(format!("Invalid policy: {}", e)))?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Checks what deserialization cannot: every rule must deny or require.
+    pub fn validate(&self) -> Result<()> {
+        for rule in &self.rules {
+            if !rule.deny && rule.require.is_empty() {
+                return Err(GitMcpError::ConfigError(format!(
+                    "Policy rule {} needs either \"deny\" or \"require\"",
+                    rule.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the allow/deny lists let `tool_name` be called at all.
+    pub fn allows_tool(&self, tool_name: &str) -> bool {
+        self.tool_list_violation(tool_name).is_none()
+    }
+
+    fn tool_list_violation(&self, tool_name: &str) -> Option<GitMcpError> {
+        if let Some(allowed) = &self.allow_tools {
+            if !allowed.iter().any(|p| glob_match(p, tool_name)) {
+                return Some(GitMcpError::PolicyViolation {
+                    rule: "allow_tools".to_string(),
+                    message: format!("{} is not an allowed tool", tool_name),
+                });
+            }
+        }
+        if self.deny_tools.iter().any(|p| glob_match(p, tool_name)) {
+            return Some(GitMcpError::PolicyViolation {
+                rule: "deny_tools".to_string(),
+                message: format!("{} is a denied tool", tool_name),
+            });
+        }
+        None
+    }
+
+    /// Checks a tool call, naming the first rule it breaks.
+    pub fn check(&self, tool_name: &str, arguments: &Value) -> Result<()> {
+        if let Some(violation) = self.tool_list_violation(tool_name) {
+            return Err(violation);
+        }
+
+        for rule in &self.rules {
+            if !glob_match(&rule.tool, tool_name)
+                || !conditions_match(&rule.when, arguments, Elements::Any)
+            {
+                continue;
+            }
+
+            let failed = if rule.deny {
+                Some(format!("{} is not allowed with these arguments", tool_name))
+            } else {
+                rule.require
+                    .iter()
+                    .find(|(key, expected)| {
+                        !value_matches(expected, arguments.get(key.as_str()), Elements::All)
+                    })
+                    .map(|(key, expected)| format!("{} must match {}", key, expected))
+            };
+            if let Some(generated) = failed {
+                return Err(GitMcpError::PolicyViolation {
+                    rule: rule.name.clone(),
+                    message: rule.message.clone().unwrap_or(generated),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which elements of an array argument must match a condition.
+#[derive(Clone, Copy)]
+enum Elements {
+    Any,
+    All,
+}
+
+fn conditions_match(
+    conditions: &Map<String, Value>,
+    arguments: &Value,
+    elements: Elements,
+) -> bool {
+    conditions
+        .iter()
+        .all(|(key, expected)| value_matches(expected, arguments.get(key.as_str()), elements))
+}
+
+fn value_matches(expected: &Value, actual: Option<&Value>, elements: Elements) -> bool {
+    match (expected, actual) {
+        (_, Some(Value::Array(items))) if !items.is_empty() => {
+            let matches = |i| value_matches(expected, Some(i), elements);
+            match elements {
+                Elements::Any => items.iter().any(matches),
+                Elements::All => items.iter().all(matches),
+            }
+        }
+        (Value::Array(options), _) => options.iter().any(|o| value_matches(o, actual, elements)),
+        (Value::Null, None | Some(Value::Null)) => true,
+        (_, None) => false,
+        (Value::String(pattern), Some(Value::String(s))) => glob_match(pattern, s),
+        (expected, Some(actual)) => expected == actual,
+    }
+}
+
+/// Matches `text` against a pattern where `*` stands for any run of
+/// characters and `?` for exactly one.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{advanced, analysis, branching, history, remote, repo, staging};
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_json::json;
+
+    /// Round-trips `json` through the tool's input struct, so a rule keyed on a
+    /// misspelled or renamed field fails to match.
+    fn input<T: DeserializeOwned + Serialize>(json: Value) -> Value {
+        let parsed: T = serde_json::from_value(json).expect("valid tool input");
+        serde_json::to_value(parsed).unwrap()
+    }
+
+    fn deny_when(tool: &str, when: Value) -> Policy {
+        Policy::from_json(
+            &json!({"rules": [{"name": "rule", "tool": tool, "when": when, "deny": true}]})
+                .to_string(),
+        )
+        .unwrap()
+    }
+
+    fn violated_rule(policy: &Policy, tool: &str, args: &Value) -> Option<String> {
+        match policy.check(tool, args) {
+            Ok(()) => None,
+            Err(GitMcpError::PolicyViolation { rule, .. }) => Some(rule),
+            Err(e) => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn rules_match_every_tool_input() {
+        let cases = [
+            (
+                "git_status",
+                json!({"include_untracked": true}),
+                input::<repo::GitStatusInput>(json!({"include_untracked": true})),
+            ),
+            (
+                "git_init",
+                json!({"bare": true}),
+                input::<repo::GitInitInput>(json!({"path": "r", "bare": true})),
+            ),
+            (
+                "git_clone",
+                json!({"url": "https://*"}),
+                input::<repo::GitCloneInput>(
+                    json!({"url": "https://example.com/r.git", "local_path": "r"}),
+                ),
+            ),
+            (
+                "git_clean",
+                json!({"directories": true}),
+                input::<repo::GitCleanInput>(json!({"force": true, "directories": true})),
+            ),
+            (
+                "git_add",
+                json!({"files": "secrets/*"}),
+                input::<staging::GitAddInput>(json!({"files": ["secrets/key"]})),
+            ),
+            (
+                "git_commit",
+                json!({"no_verify": true}),
+                input::<staging::GitCommitInput>(json!({"message": "m", "no_verify": true})),
+            ),
+            (
+                "git_diff",
+                json!({"staged": true}),
+                input::<staging::GitDiffInput>(json!({"staged": true})),
+            ),
+            (
+                "git_log",
+                json!({"author": "bot*"}),
+                input::<history::GitLogInput>(json!({"author": "bot@example.com"})),
+            ),
+            (
+                "git_show",
+                json!({"object": "HEAD*"}),
+                input::<history::GitShowInput>(json!({"object": "HEAD~1"})),
+            ),
+            (
+                "git_blame",
+                json!({"file": "*.rs"}),
+                input::<history::GitBlameInput>(json!({"file": "src/main.rs"})),
+            ),
+            (
+                "git_reflog",
+                json!({"max_count": 1000}),
+                input::<history::GitReflogInput>(json!({"max_count": 1000})),
+            ),
+            (
+                "git_verify",
+                json!({"objects": "v*"}),
+                input::<history::GitVerifyInput>(json!({"objects": ["v1.0.0"]})),
+            ),
+            (
+                "git_branch",
+                json!({"operation": "delete"}),
+                input::<branching::GitBranchInput>(json!({"operation": "delete", "name": "main"})),
+            ),
+            (
+                "git_checkout",
+                json!({"force": true}),
+                input::<branching::GitCheckoutInput>(json!({"target": "main", "force": true})),
+            ),
+            (
+                "git_merge",
+                json!({"squash": true}),
+                input::<branching::GitMergeInput>(json!({"branch": "dev", "squash": true})),
+            ),
+            (
+                "git_rebase",
+                json!({"interactive": true}),
+                input::<branching::GitRebaseInput>(
+                    json!({"upstream": "main", "interactive": true}),
+                ),
+            ),
+            (
+                "git_cherry_pick",
+                json!({"no_commit": true}),
+                input::<branching::GitCherryPickInput>(
+                    json!({"commits": ["abc"], "no_commit": true}),
+                ),
+            ),
+            (
+                "git_remote",
+                json!({"mode": "set-url"}),
+                input::<remote::GitRemoteInput>(json!({"mode": "set-url", "name": "origin"})),
+            ),
+            (
+                "git_fetch",
+                json!({"prune": true}),
+                input::<remote::GitFetchInput>(json!({"prune": true})),
+            ),
+            (
+                "git_pull",
+                json!({"rebase": true}),
+                input::<remote::GitPullInput>(json!({"rebase": true})),
+            ),
+            (
+                "git_push",
+                json!({"force": true}),
+                input::<remote::GitPushInput>(json!({"force": true})),
+            ),
+            (
+                "git_tag",
+                json!({"mode": "delete"}),
+                input::<advanced::GitTagInput>(json!({"mode": "delete", "tag_name": "v1"})),
+            ),
+            (
+                "git_stash",
+                json!({"mode": ["drop", "clear"]}),
+                input::<advanced::GitStashInput>(json!({"mode": "clear"})),
+            ),
+            (
+                "git_reset",
+                json!({"mode": "hard"}),
+                input::<advanced::GitResetInput>(json!({"mode": "hard"})),
+            ),
+            (
+                "git_worktree",
+                json!({"mode": "remove"}),
+                input::<advanced::GitWorktreeInput>(
+                    json!({"mode": "remove", "worktree_path": "wt"}),
+                ),
+            ),
+            (
+                "git_set_working_dir",
+                json!({"path": "/etc*"}),
+                input::<advanced::GitSetWorkingDirInput>(json!({"path": "/etc"})),
+            ),
+            (
+                "git_clear_working_dir",
+                json!({"confirm": "Y*"}),
+                input::<advanced::GitClearWorkingDirInput>(json!({"confirm": "Yes"})),
+            ),
+            (
+                "git_changelog_analyze",
+                json!({"maxCommits": 500}),
+                input::<analysis::GitChangelogAnalyzeInput>(
+                    json!({"path": ".", "reviewTypes": ["security"], "maxCommits": 500}),
+                ),
+            ),
+            (
+                "git_wrapup_instructions",
+                json!({"createTag": true}),
+                input::<advanced::GitWrapupInstructionsInput>(
+                    json!({"acknowledgement": "Y", "createTag": true}),
+                ),
+            ),
+        ];
+
+        for (tool, when, args) in cases {
+            let policy = deny_when(tool, when.clone());
+            assert_eq!(
+                violated_rule(&policy, tool, &args).as_deref(),
+                Some("rule"),
+                "{} with {} should be denied",
+                tool,
+                when
+            );
+            let other = deny_when("git_none", when);
+            assert_eq!(violated_rule(&other, tool, &args), None, "{}", tool);
+        }
+    }
+
+    #[test]
+    fn absent_arguments_do_not_trigger_rules() {
+        let policy = deny_when("git_push", json!({"force": true}));
+        let args = input::<remote::GitPushInput>(json!({"remote": "origin"}));
+        assert_eq!(violated_rule(&policy, "git_push", &args), None);
+        assert_eq!(violated_rule(&policy, "git_push", &json!({})), None);
+    }
+
+    #[test]
+    fn require_restricts_branch_deletes() {
+        let policy = Policy::from_json(
+            &json!({"rules": [{
+                "name": "agent-branches",
+                "tool": "git_branch",
+                "when": {"operation": "delete"},
+                "require": {"name": "agent/*"},
+                "message": "only agent/* branches may be deleted"
+            }]})
+            .to_string(),
+        )
+        .unwrap();
+
+        let delete = |name: &str| {
+            input::<branching::GitBranchInput>(json!({"operation": "delete", "name": name}))
+        };
+        assert_eq!(
+            violated_rule(&policy, "git_branch", &delete("agent/fix")),
+            None
+        );
+        match policy.check("git_branch", &delete("main")) {
+            Err(GitMcpError::PolicyViolation { rule, message }) => {
+                assert_eq!(rule, "agent-branches");
+                assert_eq!(message, "only agent/* branches may be deleted");
+            }
+            other => panic!("expected a violation, got {:?}", other),
+        }
+        let create =
+            input::<branching::GitBranchInput>(json!({"operation": "create", "name": "main"}));
+        assert_eq!(violated_rule(&policy, "git_branch", &create), None);
+    }
+
+    #[test]
+    fn array_arguments() {
+        let policy = Policy::from_json(
+            &json!({"rules": [
+                {"name": "no-secrets", "tool": "git_add", "when": {"files": "secrets/*"},
+                 "deny": true},
+                {"name": "src-only", "tool": "git_commit",
+                 "require": {"files_to_stage": ["src/*", "tests/*"]}}
+            ]})
+            .to_string(),
+        )
+        .unwrap();
+
+        let add = |files: Value| input::<staging::GitAddInput>(json!({"files": files}));
+        assert_eq!(
+            violated_rule(
+                &policy,
+                "git_add",
+                &add(json!(["secrets/key", "README.md"]))
+            ),
+            Some("no-secrets".to_string())
+        );
+        assert_eq!(
+            violated_rule(&policy, "git_add", &add(json!(["README.md"]))),
+            None
+        );
+
+        let commit = |files: Value| {
+            input::<staging::GitCommitInput>(json!({"message": "m", "files_to_stage": files}))
+        };
+        assert_eq!(
+            violated_rule(
+                &policy,
+                "git_commit",
+                &commit(json!(["src/a.rs", "tests/b.rs"]))
+            ),
+            None
+        );
+        assert_eq!(
+            violated_rule(
+                &policy,
+                "git_commit",
+                &commit(json!(["src/a.rs", "README.md"]))
+            ),
+            Some("src-only".to_string())
+        );
+    }
+
+    #[test]
+    fn tool_lists() {
+        let policy = Policy::from_json(
+            &json!({"allow_tools": ["git_*"], "deny_tools": ["git_clean", "git_re*"]}).to_string(),
+        )
+        .unwrap();
+        assert!(policy.allows_tool("git_status"));
+        assert!(!policy.allows_tool("git_clean"));
+        assert!(!policy.allows_tool("git_reset"));
+        assert!(!policy.allows_tool("other_tool"));
+        assert_eq!(
+            violated_rule(&policy, "git_reset", &json!({})).as_deref(),
+            Some("deny_tools")
+        );
+        assert_eq!(
+            violated_rule(&policy, "other_tool", &json!({})).as_deref(),
+            Some("allow_tools")
+        );
+        assert!(Policy::default().allows_tool("git_clean"));
+    }
+
+    #[test]
+    fn invalid_policies_are_config_errors() {
+        for bad in [
+            r#"{"rules": [{"name": "noop", "tool": "git_push"}]}"#,
+            r#"{"rules": [{"tool": "git_push", "deny": true}]}"#,
+            r#"{"deny_tool": ["git_push"]}"#,
+            "not json",
+        ] {
+            assert!(
+                matches!(Policy::from_json(bad), Err(GitMcpError::ConfigError(_))),
+                "{} should be rejected",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("agent/*", "agent/a/b"));
+        assert!(glob_match("release-?.?", "release-1.2"));
+        assert!(glob_match("*-wip", "feature-wip"));
+        assert!(!glob_match("agent/*", "main"));
+        assert!(!glob_match("release-?", "release-10"));
+        assert!(glob_match("a*b*c", "aXXbYYc"));
+        assert!(!glob_match("a*b*c", "aXXbYY"));
+    }
+}
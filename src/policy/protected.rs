@@ -0,0 +1,432 @@
+use crate::error::{GitMcpError, Result};
+use crate::tools::ToolContext;
+use serde_json::Value;
+use std::path::Path;
+
+/// A ref a tool call would rewrite or delete, as named by its arguments.
+#[derive(Debug, PartialEq)]
+enum Target {
+    Branch(String),
+    Tag(String),
+    /// A push refspec without `:`, which updates the remote ref the source
+    /// resolves to, e.g. the current branch for `HEAD`.
+    PushSource(String),
+    /// The remote side of a push refspec; short names may be either kind.
+    /// Wildcard refspecs stand for every ref they expand to.
+    PushDestination(String),
+    CurrentBranch,
+    AllTags,
+}
+
+/// The refs a call would rewrite or delete, each with a description of the
+/// operation for the error message.
+fn targets(tool_name: &str, arguments: &Value) -> Vec<(Target, &'static str)> {
+    let arg_str = |key: &str| arguments.get(key).and_then(|v| v.as_str());
+    let arg_bool = |key: &str| {
+        arguments
+            .get(key)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    };
+
+    match tool_name {
+        "git_push" if !arg_bool("dry_run") => {
+            let mut force = arg_bool("force") || arg_bool("force_with_lease");
+            let mut delete = arg_bool("delete");
+            let destination = arg_str("branch").and_then(|refspec| {
+                let refspec = match refspec.strip_prefix('+') {
+                    Some(rest) => {
+                        force = true;
+                        rest
+                    }
+                    None => refspec,
+                };
+                match refspec.split_once(':') {
+                    Some((source, destination)) => {
+                        delete |= source.is_empty();
+                        (!destination.is_empty())
+                            .then(|| Target::PushDestination(destination.to_string()))
+                    }
+                    // `--delete` names remote refs directly.
+                    None if delete => Some(Target::PushDestination(refspec.to_string())),
+                    None => (!refspec.is_empty()).then(|| Target::PushSource(refspec.to_string())),
+                }
+            });
+
+            let mut targets = Vec::new();
+            if delete {
+                if let Some(destination) = destination {
+                    targets.push((destination, "remote delete"));
+                }
+            } else if force {
+                targets.push((destination.unwrap_or(Target::CurrentBranch), "force push"));
+                if arg_bool("tags") {
+                    targets.push((Target::AllTags, "force push"));
+                }
+            }
+            targets
+        }
+        "git_branch" => {
+            let name = arg_str("name").map(str::to_string);
+            match (arg_str("operation"), name) {
+                (Some("delete"), Some(name)) => vec![(Target::Branch(name), "branch delete")],
+                (Some("create"), Some(name)) if arg_bool("force") => {
+                    vec![(Target::Branch(name), "forced branch create")]
+                }
+                // `git branch -m <new>` renames the current branch.
+                (Some("rename"), Some(name)) if arg_str("new_name").is_some() => {
+                    vec![(Target::Branch(name), "branch rename")]
+                }
+                (Some("rename"), _) => vec![(Target::CurrentBranch, "branch rename")],
+                _ => Vec::new(),
+            }
+        }
+        // Every mode moves the current branch; only a reset of paths does not.
+        "git_reset" if arguments.get("paths").is_none_or(Value::is_null) => {
+            let operation = match arg_str("mode") {
+                Some("hard") => "hard reset",
+                Some("soft") => "soft reset",
+                Some("merge") => "merge reset",
+                Some("keep") => "keep reset",
+                _ => "mixed reset",
+            };
+            vec![(Target::CurrentBranch, operation)]
+        }
+        "git_pull" if arg_bool("rebase") => vec![(Target::CurrentBranch, "pull rebase")],
+        "git_commit" if arg_bool("amend") => vec![(Target::CurrentBranch, "amend")],
+        "git_rebase" if !matches!(arg_str("mode"), Some("continue" | "abort" | "skip")) => {
+            match arg_str("branch") {
+                Some(branch) => vec![(Target::Branch(branch.to_string()), "rebase")],
+                None => vec![(Target::CurrentBranch, "rebase")],
+            }
+        }
+        "git_tag" => {
+            let name = arg_str("tag_name").map(str::to_string);
+            match (arg_str("mode"), name) {
+                (Some("create"), Some(name)) if arg_bool("force") => {
+                    vec![(Target::Tag(name), "forced tag overwrite")]
+                }
+                (Some("delete"), Some(name)) => vec![(Target::Tag(name), "tag delete")],
+                _ => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The pattern protecting `refname` (a full ref such as `refs/heads/main`, or
+/// a wildcard refspec side such as `refs/heads/*`, which is protected if it
+/// can expand to a protected ref). Patterns starting with `refs/` match full
+/// names; others match the branch or tag name.
+fn protecting_pattern<'a>(patterns: &'a [String], refname: &str) -> Option<&'a str> {
+    patterns
+        .iter()
+        .find(|pattern| {
+            if pattern.starts_with("refs/") {
+                globs_overlap(pattern, refname)
+            } else {
+                ["refs/heads/", "refs/tags/"]
+                    .iter()
+                    .any(|prefix| globs_overlap(&format!("{}{}", prefix, pattern), refname))
+            }
+        })
+        .map(String::as_str)
+}
+
+/// Whether some string matches both glob patterns. Without wildcards in `b`
+/// (refnames cannot contain `*` or `?`) this is a plain glob match.
+fn globs_overlap(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // overlap[i][j]: the suffixes a[i..] and b[j..] share a match.
+    let mut overlap = vec![vec![false; b.len() + 1]; a.len() + 1];
+    for i in (0..=a.len()).rev() {
+        for j in (0..=b.len()).rev() {
+            overlap[i][j] = if i == a.len() && j == b.len() {
+                true
+            } else if a.get(i) == Some(&'*') {
+                overlap[i + 1][j] || (j < b.len() && overlap[i][j + 1])
+            } else if b.get(j) == Some(&'*') {
+                overlap[i][j + 1] || (i < a.len() && overlap[i + 1][j])
+            } else if i < a.len() && j < b.len() {
+                (a[i] == b[j] || a[i] == '?' || b[j] == '?') && overlap[i + 1][j + 1]
+            } else {
+                false
+            };
+        }
+    }
+    overlap[0][0]
+}
+
+fn qualify(name: &str, prefix: &str) -> String {
+    if name.starts_with("refs/") {
+        name.to_string()
+    } else {
+        format!("{}{}", prefix, name)
+    }
+}
+
+/// The refs a push destination may name: short names may be either kind.
+fn destination_refs(name: &str) -> Vec<String> {
+    if name.starts_with("refs/") {
+        vec![name.to_string()]
+    } else {
+        vec![
+            format!("refs/heads/{}", name),
+            format!("refs/tags/{}", name),
+        ]
+    }
+}
+
+/// Refuses calls that would rewrite or delete a ref matching
+/// `MCP_PROTECTED_REFS`.
+pub async fn check_protected_refs(
+    ctx: &ToolContext,
+    tool_name: &str,
+    arguments: &Value,
+) -> Result<()> {
+    let patterns = &ctx.config.protected_refs;
+    if patterns.is_empty() {
+        return Ok(());
+    }
+
+    for (target, operation) in targets(tool_name, arguments) {
+        let refnames = match target {
+            Target::Branch(name) => vec![qualify(&name, "refs/heads/")],
+            Target::Tag(name) => vec![qualify(&name, "refs/tags/")],
+            Target::PushDestination(name) => destination_refs(&name),
+            // git pushes a symbolic source such as `HEAD` to the ref it points
+            // at. Wildcards and option-like names are not resolved.
+            Target::PushSource(name) => {
+                let mut refnames = destination_refs(&name);
+                if !name.contains('*') && !name.starts_with('-') {
+                    let args = ["rev-parse", "--symbolic-full-name", name.as_str()];
+                    refnames.extend(git_lines(ctx, arguments, &args, operation).await?);
+                }
+                refnames
+            }
+            // A detached HEAD moves no branch.
+            Target::CurrentBranch => {
+                git_lines(ctx, arguments, &["branch", "--show-current"], operation)
+                    .await?
+                    .iter()
+                    .map(|name| qualify(name, "refs/heads/"))
+                    .collect()
+            }
+            Target::AllTags => {
+                git_lines(
+                    ctx,
+                    arguments,
+                    &["for-each-ref", "--format=%(refname)", "refs/tags"],
+                    operation,
+                )
+                .await?
+            }
+        };
+
+        for refname in refnames {
+            if let Some(pattern) = protecting_pattern(patterns, &refname) {
+                return Err(GitMcpError::PolicyViolation {
+                    rule: "protected_refs".to_string(),
+                    message: format!(
+                        "{} would rewrite or delete {}, which is protected by pattern '{}' in MCP_PROTECTED_REFS",
+                        operation, refname, pattern
+                    ),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Non-empty output lines of a read-only git command in the repository the
+/// call targets. If it fails the refs are unknown, so the call is refused.
+async fn git_lines(
+    ctx: &ToolContext,
+    arguments: &Value,
+    args: &[&str],
+    operation: &str,
+) -> Result<Vec<String>> {
+    let executor = ctx.executor.read().await;
+    let output = match arguments.get("path").and_then(|v| v.as_str()) {
+        Some(path) => executor.execute_in_dir(Path::new(path), args).await,
+        None => executor.execute(args).await,
+    };
+    match output {
+        Ok(output) => Ok(output
+            .lines()
+            .iter()
+            .filter(|l| !l.is_empty())
+            .map(|l| l.to_string())
+            .collect()),
+        Err(e) => Err(GitMcpError::PolicyViolation {
+            rule: "protected_refs".to_string(),
+            message: format!(
+                "cannot tell which refs {} would rewrite or delete: {}",
+                operation, e
+            ),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn push_refspecs() {
+        assert_eq!(
+            targets("git_push", &json!({"force": true})),
+            vec![(Target::CurrentBranch, "force push")]
+        );
+        assert_eq!(
+            targets("git_push", &json!({"branch": "+feature:main"})),
+            vec![(Target::PushDestination("main".into()), "force push")]
+        );
+        assert_eq!(
+            targets("git_push", &json!({"branch": ":main"})),
+            vec![(Target::PushDestination("main".into()), "remote delete")]
+        );
+        assert_eq!(
+            targets("git_push", &json!({"branch": "main", "delete": true})),
+            vec![(Target::PushDestination("main".into()), "remote delete")]
+        );
+        assert_eq!(
+            targets(
+                "git_push",
+                &json!({"force": true, "tags": true, "branch": "dev"})
+            ),
+            vec![
+                (Target::PushSource("dev".into()), "force push"),
+                (Target::AllTags, "force push")
+            ]
+        );
+        assert_eq!(
+            targets("git_push", &json!({"branch": "+HEAD"})),
+            vec![(Target::PushSource("HEAD".into()), "force push")]
+        );
+        assert_eq!(
+            targets("git_push", &json!({"branch": "+refs/heads/*:refs/heads/*"})),
+            vec![(Target::PushDestination("refs/heads/*".into()), "force push")]
+        );
+        assert!(targets("git_push", &json!({"branch": "main"})).is_empty());
+        assert!(targets("git_push", &json!({"force": true, "dry_run": true})).is_empty());
+    }
+
+    #[test]
+    fn local_rewrites() {
+        let branch = |name: &str| Target::Branch(name.into());
+        assert_eq!(
+            targets(
+                "git_branch",
+                &json!({"operation": "delete", "name": "main"})
+            ),
+            vec![(branch("main"), "branch delete")]
+        );
+        assert!(targets(
+            "git_branch",
+            &json!({"operation": "create", "name": "main"})
+        )
+        .is_empty());
+        assert_eq!(
+            targets(
+                "git_branch",
+                &json!({"operation": "rename", "name": "trunk"})
+            ),
+            vec![(Target::CurrentBranch, "branch rename")]
+        );
+        assert_eq!(
+            targets("git_reset", &json!({"mode": "hard"})),
+            vec![(Target::CurrentBranch, "hard reset")]
+        );
+        assert_eq!(
+            targets("git_reset", &json!({"mode": "soft"})),
+            vec![(Target::CurrentBranch, "soft reset")]
+        );
+        assert_eq!(
+            targets("git_reset", &json!({})),
+            vec![(Target::CurrentBranch, "mixed reset")]
+        );
+        assert!(targets("git_reset", &json!({"paths": ["a.txt"]})).is_empty());
+        assert_eq!(
+            targets("git_pull", &json!({"rebase": true})),
+            vec![(Target::CurrentBranch, "pull rebase")]
+        );
+        assert!(targets("git_pull", &json!({})).is_empty());
+        assert_eq!(
+            targets("git_commit", &json!({"message": "m", "amend": true})),
+            vec![(Target::CurrentBranch, "amend")]
+        );
+        assert!(targets("git_commit", &json!({"message": "m"})).is_empty());
+        assert_eq!(
+            targets(
+                "git_rebase",
+                &json!({"upstream": "main", "branch": "release"})
+            ),
+            vec![(branch("release"), "rebase")]
+        );
+        assert!(targets("git_rebase", &json!({"mode": "abort"})).is_empty());
+        assert_eq!(
+            targets(
+                "git_tag",
+                &json!({"mode": "create", "tag_name": "v1", "force": true})
+            ),
+            vec![(Target::Tag("v1".into()), "forced tag overwrite")]
+        );
+        assert!(targets("git_tag", &json!({"mode": "create", "tag_name": "v1"})).is_empty());
+    }
+
+    #[test]
+    fn patterns_match_short_and_full_names() {
+        let patterns = vec![
+            "main".to_string(),
+            "release/*".to_string(),
+            "refs/tags/v*".to_string(),
+        ];
+        assert_eq!(
+            protecting_pattern(&patterns, "refs/heads/main"),
+            Some("main")
+        );
+        assert_eq!(
+            protecting_pattern(&patterns, "refs/heads/release/1.0"),
+            Some("release/*")
+        );
+        assert_eq!(
+            protecting_pattern(&patterns, "refs/tags/v1.2"),
+            Some("refs/tags/v*")
+        );
+        assert_eq!(protecting_pattern(&patterns, "refs/heads/v1.2"), None);
+        assert_eq!(
+            protecting_pattern(&patterns, "refs/heads/feature/main"),
+            None
+        );
+        assert_eq!(
+            protecting_pattern(&patterns, "refs/remotes/origin/main"),
+            None
+        );
+    }
+
+    #[test]
+    fn wildcard_destinations() {
+        let patterns = vec!["main".to_string(), "refs/tags/v*".to_string()];
+        let protected = |side: &str| {
+            destination_refs(side)
+                .iter()
+                .any(|r| protecting_pattern(&patterns, r).is_some())
+        };
+        for side in [
+            "refs/heads/*",
+            "refs/*",
+            "*",
+            "refs/heads/ma*",
+            "refs/tags/*",
+        ] {
+            assert!(protected(side), "{} should be protected", side);
+        }
+        assert!(!protected("refs/heads/feature/*"));
+        assert!(!protected("refs/tags/build-*"));
+        assert!(globs_overlap("release/*", "rel*/1.0"));
+        assert!(!globs_overlap("release/*", "feature/*"));
+    }
+}
@@ -1,13 +1,29 @@
-use crate::config::Config;
-use crate::tools::{advanced, analysis, branching, history, remote, repo, staging, ToolContext};
+use crate::audit;
+use crate::auth::{AuthIdentity, KeyStore, Scope, OAUTH_ALGORITHMS};
+use crate::config::{Config, LiveConfig};
+use crate::error::GitMcpError;
+use crate::git::{with_command_config, with_command_timeout};
+use crate::git::{GitProgress, ProgressSink};
+use crate::policy;
+use crate::redact::Redactor;
+use crate::session::SessionStore;
+use crate::tools::{
+    self, advanced, analysis, branching, history, remote, repo, staging, Notifier, ToolContext,
+};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::{FutureExt, Stream};
 use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
-use schemars::schema_for;
+use schemars::gen::SchemaSettings;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::value::RawValue;
 use serde_json::Map;
 use serde_json::Value;
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot, watch, Semaphore};
 
 type ResponseBuffer = Vec<u8>;
 
@@ -15,131 +31,293 @@ thread_local! {
     static RESPONSE_BUFFER: RefCell<ResponseBuffer> = RefCell::new(Vec::with_capacity(8192));
 }
 
-pub async fn run_server(config: Config) -> anyhow::Result<()> {
-    let ctx = ToolContext::new(config.clone());
+/// Notification telling clients to fetch tools/list again.
+const TOOLS_LIST_CHANGED: &str = r#"{"jsonrpc":"2.0","method":"notifications/tools/list_changed"}"#;
 
-    match config.transport_type {
+pub async fn run_server(live: LiveConfig) -> anyhow::Result<()> {
+    match live.current().transport_type {
         crate::config::TransportType::Http => {
             tracing::info!("Running in HTTP mode");
-            run_http_server(ctx).await
+            run_http_server(live).await
         }
         crate::config::TransportType::Stdio => {
             tracing::info!("Running in STDIO mode");
-            run_stdio_server(ctx).await
+            run_stdio_server(live).await
         }
     }
 }
 
-async fn run_http_server(ctx: ToolContext) -> anyhow::Result<()> {
+/// Calls `announce` after every reload that changes the tools on offer.
+fn announce_tool_changes<F, Fut>(mut live: LiveConfig, announce: F) -> tokio::task::JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = ()> + Send,
+{
+    tokio::spawn(async move {
+        let mut offered = offered_tools(&live.current());
+        while let Some(config) = live.changed().await {
+            let now_offered = offered_tools(&config);
+            if now_offered != offered {
+                tracing::info!("Tool list changed");
+                offered = now_offered;
+                announce().await;
+            }
+        }
+    })
+}
+
+async fn run_http_server(live: LiveConfig) -> anyhow::Result<()> {
     use axum::extract::State;
     use axum::http::{HeaderMap, HeaderValue, StatusCode};
-    use axum::response::IntoResponse;
-    use axum::routing::post;
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::{get, post};
     use axum::Router;
-    use std::collections::HashMap;
     use std::net::SocketAddr;
-    use std::sync::Arc;
-    use tokio::sync::RwLock;
-    use uuid::Uuid;
 
     #[derive(Clone)]
     struct AppState {
-        config: Arc<Config>,
+        live: LiveConfig,
         session_mode: crate::config::SessionMode,
-        sessions: Arc<RwLock<HashMap<String, ToolContext>>>,
+        sessions: Arc<SessionStore>,
+        in_flight: InFlightRequests,
+        scheduler: Arc<RepoScheduler>,
+        limit: Arc<Semaphore>,
+        keys: Arc<KeyStore>,
     }
 
-    async fn handler(
-        State(state): State<AppState>,
-        headers: HeaderMap,
-        body: String,
-    ) -> impl IntoResponse {
+    fn base_headers(headers: &HeaderMap) -> HeaderMap {
         let protocol_version = headers
             .get("MCP-Protocol-Version")
             .and_then(|v| v.to_str().ok())
-            .unwrap_or("2025-11-25")
-            .to_string();
-
-        let session_id = headers
-            .get("MCP-Session-Id")
-            .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_string());
-
-        let parsed: Result<JsonRpcRequest, _> = serde_json::from_str(&body);
-        let req = match parsed {
-            Ok(r) => r,
-            Err(e) => {
-                let resp = json_rpc_error(None, &format!("Parse error: {}", e), -32700);
-                let mut out_headers = HeaderMap::new();
-                out_headers.insert("Content-Type", HeaderValue::from_static("application/json"));
-                out_headers.insert(
-                    "MCP-Protocol-Version",
-                    HeaderValue::from_str(&protocol_version)
-                        .unwrap_or(HeaderValue::from_static("2025-11-25")),
-                );
-                return (StatusCode::OK, out_headers, resp);
-            }
-        };
-
-        let mut response_headers = HeaderMap::new();
-        response_headers.insert("Content-Type", HeaderValue::from_static("application/json"));
-        response_headers.insert(
+            .unwrap_or("2025-11-25");
+        let mut out = HeaderMap::new();
+        out.insert(
             "MCP-Protocol-Version",
-            HeaderValue::from_str(&protocol_version)
+            HeaderValue::from_str(protocol_version)
                 .unwrap_or(HeaderValue::from_static("2025-11-25")),
         );
+        out
+    }
 
-        if !state.config.allowed_origins.is_empty() {
+    fn session_id(headers: &HeaderMap) -> Option<String> {
+        headers
+            .get("MCP-Session-Id")
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    }
+
+    /// Origin and authentication checks shared by every method on the endpoint.
+    /// Authentication failures add a Bearer challenge to `response_headers`.
+    async fn check_access(
+        state: &AppState,
+        config: &Config,
+        headers: &HeaderMap,
+        response_headers: &mut HeaderMap,
+    ) -> Result<Option<AuthIdentity>, (StatusCode, String, i32)> {
+        if !config.allowed_origins.is_empty() {
             if let Some(origin) = headers.get("Origin").and_then(|v| v.to_str().ok()) {
-                if !state
-                    .config
+                if !config
                     .allowed_origins
                     .iter()
                     .any(|allowed| allowed == origin)
                 {
-                    let resp = json_rpc_error(req.id.as_ref(), "Forbidden origin", -32003);
-                    return (StatusCode::FORBIDDEN, response_headers, resp);
+                    return Err((
+                        StatusCode::FORBIDDEN,
+                        "Forbidden origin".to_string(),
+                        -32003,
+                    ));
                 }
             }
         }
+        validate_auth(config, &state.keys, headers)
+            .await
+            .map_err(|msg| {
+                if let Ok(challenge) =
+                    HeaderValue::from_str(&bearer_challenge(config, headers, &msg))
+                {
+                    response_headers.insert("WWW-Authenticate", challenge);
+                }
+                (StatusCode::UNAUTHORIZED, msg, -32001)
+            })
+    }
+
+    /// RFC 9728 metadata naming the authorization server that issues tokens
+    /// for this endpoint. Only served in oauth mode.
+    async fn resource_metadata_handler(
+        State(state): State<AppState>,
+        headers: HeaderMap,
+    ) -> Response {
+        let config = state.live.current();
+        if config.auth_mode != crate::config::AuthMode::Oauth {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        let metadata = serde_json::json!({
+            "resource": resource_url(&config, &headers),
+            "authorization_servers": config.oauth_issuer_url.iter().collect::<Vec<_>>(),
+            "bearer_methods_supported": ["header"],
+            "scopes_supported": Scope::ALL.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+        });
+        axum::Json(metadata).into_response()
+    }
 
-        if let Err(msg) = validate_auth(&state.config, &headers) {
-            let resp = json_rpc_error(req.id.as_ref(), &msg, -32001);
-            return (StatusCode::UNAUTHORIZED, response_headers, resp);
+    fn error_response(
+        mut headers: HeaderMap,
+        status: StatusCode,
+        message: &str,
+        code: i32,
+    ) -> Response {
+        headers.insert("Content-Type", HeaderValue::from_static("application/json"));
+        (status, headers, json_rpc_error(None, message, code)).into_response()
+    }
+
+    /// Opens the session's stream for server-initiated messages.
+    async fn stream_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+        let mut response_headers = base_headers(&headers);
+        let config = state.live.current();
+        if let Err((status, msg, code)) =
+            check_access(&state, &config, &headers, &mut response_headers).await
+        {
+            return error_response(response_headers, status, &msg, code);
+        }
+        if !accepts_event_stream(&headers) {
+            return error_response(
+                response_headers,
+                StatusCode::NOT_ACCEPTABLE,
+                "GET requires Accept: text/event-stream",
+                -32600,
+            );
+        }
+        if state.session_mode == crate::config::SessionMode::Stateless {
+            return (StatusCode::METHOD_NOT_ALLOWED, response_headers).into_response();
+        }
+        let Some(id) = session_id(&headers) else {
+            return error_response(
+                response_headers,
+                StatusCode::BAD_REQUEST,
+                "Missing MCP-Session-Id",
+                -32602,
+            );
+        };
+        let events = match state.sessions.get(&id).await {
+            Some(session) => session.events.subscribe(),
+            None => {
+                return error_response(
+                    response_headers,
+                    StatusCode::NOT_FOUND,
+                    "Invalid MCP session",
+                    -32602,
+                )
+            }
+        };
+        (response_headers, broadcast_stream(events)).into_response()
+    }
+
+    /// Terminates a session, cancelling whatever it still has in flight.
+    async fn delete_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+        let mut response_headers = base_headers(&headers);
+        let config = state.live.current();
+        if let Err((status, msg, code)) =
+            check_access(&state, &config, &headers, &mut response_headers).await
+        {
+            return error_response(response_headers, status, &msg, code);
         }
+        if state.session_mode == crate::config::SessionMode::Stateless {
+            return (StatusCode::METHOD_NOT_ALLOWED, response_headers).into_response();
+        }
+        let Some(id) = session_id(&headers) else {
+            return error_response(
+                response_headers,
+                StatusCode::BAD_REQUEST,
+                "Missing MCP-Session-Id",
+                -32602,
+            );
+        };
+        if !state.sessions.remove(&id).await {
+            return error_response(
+                response_headers,
+                StatusCode::NOT_FOUND,
+                "Invalid MCP session",
+                -32602,
+            );
+        }
+        state.in_flight.cancel_scope(&id);
+        (StatusCode::NO_CONTENT, response_headers).into_response()
+    }
+
+    async fn handler(State(state): State<AppState>, headers: HeaderMap, body: String) -> Response {
+        let mut response_headers = base_headers(&headers);
+        let session_id = session_id(&headers);
+
+        response_headers.insert("Content-Type", HeaderValue::from_static("application/json"));
+        let message = match parse_message(&body) {
+            Ok(message) => message,
+            Err(resp) => return (StatusCode::OK, response_headers, resp).into_response(),
+        };
+        // Errors about the HTTP request as a whole answer the request's id when
+        // there is exactly one.
+        let req_id = match &message {
+            IncomingMessage::Single(req) => req.id.clone(),
+            IncomingMessage::Batch(_) => None,
+        };
+
+        // The whole request works with the configuration current as it arrives.
+        let config = state.live.current();
+        let identity = match check_access(&state, &config, &headers, &mut response_headers).await {
+            Ok(identity) => identity,
+            Err((status, msg, code)) => {
+                let resp = json_rpc_error(req_id.as_ref(), &msg, code);
+                return (status, response_headers, resp).into_response();
+            }
+        };
 
-        let (ctx_for_call, new_session_id) = if req.method == "initialize"
+        let is_initialize =
+            matches!(&message, IncomingMessage::Single(req) if req.method == "initialize");
+        let (ctx_for_call, session_scope, new_session_id) = if is_initialize
             && state.session_mode != crate::config::SessionMode::Stateless
         {
-            let new_id = Uuid::new_v4().to_string();
-            let new_ctx = ToolContext::from_shared(state.config.clone());
-            {
-                let mut sessions = state.sessions.write().await;
-                sessions.insert(new_id.clone(), new_ctx.clone());
+            match state.sessions.create().await {
+                Ok((new_id, session)) => (session.ctx, Some(new_id.clone()), Some(new_id)),
+                Err(e) => {
+                    tracing::warn!("Rejected initialize: {}", e);
+                    let resp =
+                        json_rpc_error(req_id.as_ref(), &e.to_string(), SESSION_LIMIT_REACHED);
+                    return (StatusCode::SERVICE_UNAVAILABLE, response_headers, resp)
+                        .into_response();
+                }
             }
-            (new_ctx, Some(new_id))
         } else {
             match (state.session_mode, session_id) {
                 (crate::config::SessionMode::Stateful, Some(id))
                 | (crate::config::SessionMode::Auto, Some(id)) => {
-                    let sessions = state.sessions.read().await;
-                    if let Some(existing) = sessions.get(&id) {
-                        (existing.clone(), None)
+                    if let Some(existing) = state.sessions.get(&id).await {
+                        (existing.ctx, Some(id), None)
                     } else {
-                        let resp = json_rpc_error(req.id.as_ref(), "Invalid MCP session", -32602);
-                        return (StatusCode::OK, response_headers, resp);
+                        let resp = json_rpc_error(req_id.as_ref(), "Invalid MCP session", -32602);
+                        return (StatusCode::NOT_FOUND, response_headers, resp).into_response();
                     }
                 }
                 (crate::config::SessionMode::Stateful, None) => {
-                    let resp = json_rpc_error(req.id.as_ref(), "Missing MCP-Session-Id", -32602);
-                    return (StatusCode::OK, response_headers, resp);
+                    let resp = json_rpc_error(req_id.as_ref(), "Missing MCP-Session-Id", -32602);
+                    return (StatusCode::OK, response_headers, resp).into_response();
                 }
                 (crate::config::SessionMode::Stateless, _) => {
-                    (ToolContext::from_shared(state.config.clone()), None)
+                    (ToolContext::from_shared(config.clone()), None, None)
                 }
-                (_, None) => (ToolContext::from_shared(state.config.clone()), None),
+                (_, None) => (ToolContext::from_shared(config.clone()), None, None),
             }
         };
+        // In-flight requests are keyed by session. Without one, a request gets a
+        // scope of its own, so no other client can collide with or cancel it.
+        let scope = session_scope
+            .clone()
+            .unwrap_or_else(|| format!("request-{}", uuid::Uuid::new_v4()));
+        let ctx_for_call = ctx_for_call.with_config(config);
+
+        // Sessions outlive tokens, so scopes come from each request's token.
+        let ctx_for_call = match identity {
+            Some(identity) => ctx_for_call.with_identity(identity),
+            None => ctx_for_call,
+        };
 
         if let Some(id) = &new_session_id {
             if let Ok(val) = HeaderValue::from_str(id) {
@@ -147,27 +325,92 @@ async fn run_http_server(ctx: ToolContext) -> anyhow::Result<()> {
             }
         }
 
-        let result = process_request(&ctx_for_call, &req.method, req.params.as_deref()).await;
-        let resp = match result {
-            Ok(value) => json_rpc_response(req.id.as_ref(), value),
-            Err(e) => json_rpc_error(req.id.as_ref(), &e.to_string(), -32603),
-        };
+        // Requests that may emit notifications of their own are answered with an
+        // event stream carrying those notifications followed by the response.
+        if accepts_event_stream(&headers) && message.wants_progress() {
+            response_headers.remove("Content-Type");
+            let (tx, rx) = mpsc::unbounded_channel();
+            let ctx = ctx_for_call.with_notifier(tx.clone());
+            let in_flight = state.in_flight.clone();
+            let scheduler = state.scheduler.clone();
+            let limit = state.limit.clone();
+            let sessions = state.sessions.clone();
+            let session_scope = session_scope.clone();
+            let task = tokio::spawn(async move {
+                let resp =
+                    dispatch_message(&ctx, &in_flight, &scheduler, &limit, &scope, message).await;
+                if let Some(id) = &session_scope {
+                    sessions.save_after_request(id).await;
+                }
+                if let Some(resp) = resp {
+                    let _ = tx.send(resp);
+                }
+            });
+            return (response_headers, event_stream(rx, task)).into_response();
+        }
 
-        (StatusCode::OK, response_headers, resp)
+        let resp = dispatch_message(
+            &ctx_for_call,
+            &state.in_flight,
+            &state.scheduler,
+            &state.limit,
+            &scope,
+            message,
+        )
+        .await;
+        // Keeps the working directory and idle time on disk current.
+        if let Some(id) = &session_scope {
+            state.sessions.save_after_request(id).await;
+        }
+        match resp {
+            Some(resp) => (StatusCode::OK, response_headers, resp).into_response(),
+            None => (StatusCode::ACCEPTED, response_headers, String::new()).into_response(),
+        }
     }
 
+    let config = live.current();
     let state = AppState {
-        config: ctx.config.clone(),
-        session_mode: ctx.config.session_mode,
-        sessions: Arc::new(RwLock::new(HashMap::new())),
+        live: live.clone(),
+        session_mode: config.session_mode,
+        sessions: Arc::new(SessionStore::new(live.clone())),
+        in_flight: InFlightRequests::default(),
+        scheduler: Arc::new(RepoScheduler::default()),
+        limit: Arc::new(Semaphore::new(config.max_concurrent_requests)),
+        keys: Arc::new(KeyStore::new()),
     };
+    state.sessions.load().await;
+
+    let sessions = state.sessions.clone();
+    tokio::spawn(async move {
+        // The interval follows reloads of the idle timeout.
+        loop {
+            tokio::time::sleep(sessions.sweep_interval()).await;
+            sessions.evict_idle().await;
+        }
+    });
+
+    // Without a session there is no stream to announce changes on.
+    let sessions = state.sessions.clone();
+    announce_tool_changes(live, move || {
+        let sessions = sessions.clone();
+        async move { sessions.notify_all(TOOLS_LIST_CHANGED).await }
+    });
 
-    let endpoint_path = ctx.config.http_endpoint_path.clone();
-    let router = Router::new()
-        .route(&endpoint_path, post(handler))
-        .with_state(state);
+    let endpoint_path = config.http_endpoint_path.clone();
+    let mut router = Router::new()
+        .route(
+            &endpoint_path,
+            post(handler).get(stream_handler).delete(delete_handler),
+        )
+        .route(RESOURCE_METADATA_PATH, get(resource_metadata_handler));
+    // Clients look the metadata up under the resource's path as well.
+    let resource_path = resource_metadata_path(&config);
+    if resource_path != RESOURCE_METADATA_PATH {
+        router = router.route(&resource_path, get(resource_metadata_handler));
+    }
+    let router = router.with_state(state);
 
-    let addr: SocketAddr = format!("{}:{}", ctx.config.http_host, ctx.config.http_port)
+    let addr: SocketAddr = format!("{}:{}", config.http_host, config.http_port)
         .parse()
         .map_err(|e| anyhow::anyhow!("Invalid HTTP bind address: {}", e))?;
 
@@ -175,9 +418,148 @@ async fn run_http_server(ctx: ToolContext) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn validate_auth(config: &Config, headers: &axum::http::HeaderMap) -> Result<(), String> {
+/// Where RFC 9728 protected resource metadata lives, relative to the host.
+const RESOURCE_METADATA_PATH: &str = "/.well-known/oauth-protected-resource";
+
+/// The OAuth resource identifier of the MCP endpoint: `OAUTH_AUDIENCE` when it
+/// is a URL, otherwise the endpoint as the client reached it.
+fn resource_url(config: &Config, headers: &axum::http::HeaderMap) -> String {
+    if let Some(audience) = audience_url(config) {
+        return audience.to_string();
+    }
+    let host = headers
+        .get("Host")
+        .and_then(|v| v.to_str().ok())
+        .map(|h| h.to_string())
+        .unwrap_or_else(|| format!("{}:{}", config.http_host, config.http_port));
+    let scheme = headers
+        .get("X-Forwarded-Proto")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("http");
+    format!("{}://{}{}", scheme, host, config.http_endpoint_path)
+}
+
+fn audience_url(config: &Config) -> Option<reqwest::Url> {
+    config
+        .oauth_audience
+        .as_deref()
+        .and_then(|aud| reqwest::Url::parse(aud).ok())
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// The metadata path for this server's resource: the well-known prefix
+/// followed by the resource's own path.
+fn resource_metadata_path(config: &Config) -> String {
+    let resource_path = match audience_url(config) {
+        Some(url) => url.path().to_string(),
+        None => config.http_endpoint_path.clone(),
+    };
+    format!(
+        "{}{}",
+        RESOURCE_METADATA_PATH,
+        resource_path.trim_end_matches('/')
+    )
+}
+
+/// The `WWW-Authenticate` value for a rejected request (RFC 6750). In oauth
+/// mode it points clients at the protected resource metadata.
+fn bearer_challenge(config: &Config, headers: &axum::http::HeaderMap, error: &str) -> String {
+    let mut params = Vec::new();
+    if config.auth_mode == crate::config::AuthMode::Oauth {
+        let resource = resource_url(config, headers);
+        let metadata_url = match resource.split_once("://") {
+            Some((scheme, rest)) => {
+                let authority = rest.split('/').next().unwrap_or(rest);
+                format!(
+                    "{}://{}{}",
+                    scheme,
+                    authority,
+                    resource_metadata_path(config)
+                )
+            }
+            None => resource,
+        };
+        params.push(format!("resource_metadata=\"{}\"", metadata_url));
+    }
+    // Only a request that presented a token gets an error code (RFC 6750 §3.1).
+    if headers.contains_key("Authorization") {
+        params.push("error=\"invalid_token\"".to_string());
+        params.push(format!(
+            "error_description=\"{}\"",
+            error.replace(['"', '\\'], "'")
+        ));
+    }
+    if params.is_empty() {
+        "Bearer".to_string()
+    } else {
+        format!("Bearer {}", params.join(", "))
+    }
+}
+
+fn accepts_event_stream(headers: &axum::http::HeaderMap) -> bool {
+    headers
+        .get_all("Accept")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| v.contains("text/event-stream"))
+}
+
+/// Streams queued JSON-RPC messages as SSE `message` events until every sender
+/// is gone. The producing task is aborted if the client disconnects first.
+fn event_stream(
+    rx: mpsc::UnboundedReceiver<String>,
+    task: tokio::task::JoinHandle<()>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+    impl Drop for AbortOnDrop {
+        fn drop(&mut self) {
+            self.0.abort();
+        }
+    }
+
+    let stream =
+        futures_util::stream::unfold((rx, AbortOnDrop(task)), |(mut rx, guard)| async move {
+            let message = rx.recv().await?;
+            let event = Event::default().event("message").data(message);
+            Some((Ok(event), (rx, guard)))
+        });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Streams a session's server-initiated messages as SSE `message` events for
+/// as long as the session exists.
+fn broadcast_stream(
+    rx: tokio::sync::broadcast::Receiver<String>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    use tokio::sync::broadcast::error::RecvError;
+
+    let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(message) => {
+                    let event = Event::default().event("message").data(message);
+                    return Some((Ok(event), rx));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("GET stream fell behind; dropped {} messages", skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Validates the request's bearer token, returning the caller it identifies
+/// (`None` when authentication is off).
+async fn validate_auth(
+    config: &Config,
+    keys: &KeyStore,
+    headers: &axum::http::HeaderMap,
+) -> Result<Option<AuthIdentity>, String> {
     match config.auth_mode {
-        crate::config::AuthMode::None => Ok(()),
+        crate::config::AuthMode::None => Ok(None),
         crate::config::AuthMode::Jwt => {
             let token = extract_bearer_token(headers)?;
             let secret = config
@@ -187,13 +569,16 @@ fn validate_auth(config: &Config, headers: &axum::http::HeaderMap) -> Result<(),
 
             let mut validation = Validation::new(Algorithm::HS256);
             validation.validate_aud = false;
-            decode::<Map<String, Value>>(
+            let data = decode::<Map<String, Value>>(
                 &token,
                 &DecodingKey::from_secret(secret.as_bytes()),
                 &validation,
             )
             .map_err(|e| format!("Invalid JWT token: {}", e))?;
-            Ok(())
+            Ok(Some(AuthIdentity::from_claims(
+                &data.claims,
+                config.auth_allow_unscoped_tokens,
+            )))
         }
         crate::config::AuthMode::Oauth => {
             let token = extract_bearer_token(headers)?;
@@ -205,29 +590,22 @@ fn validate_auth(config: &Config, headers: &axum::http::HeaderMap) -> Result<(),
                 .oauth_audience
                 .as_ref()
                 .ok_or_else(|| "OAUTH_AUDIENCE is required in oauth mode".to_string())?;
-            let public_key_pem = config
-                .oauth_public_key_pem
-                .as_ref()
-                .ok_or_else(|| "OAUTH_PUBLIC_KEY_PEM is required in oauth mode".to_string())?;
-
             let header = decode_header(&token).map_err(|e| format!("Invalid JWT header: {}", e))?;
-            let algorithm = header.alg;
-            if algorithm != Algorithm::RS256 {
-                return Err("OAuth token must use RS256 algorithm".to_string());
+            if !OAUTH_ALGORITHMS.contains(&header.alg) {
+                return Err("OAuth token must use RS256, ES256 or EdDSA".to_string());
             }
+            let key = keys.decoding_key(config, &header).await?;
 
-            let mut validation = Validation::new(Algorithm::RS256);
+            let mut validation = Validation::new(header.alg);
             validation.set_issuer(&[issuer.as_str()]);
             validation.set_audience(&[audience.as_str()]);
 
-            decode::<Map<String, Value>>(
-                &token,
-                &DecodingKey::from_rsa_pem(public_key_pem.as_bytes())
-                    .map_err(|e| format!("Invalid OAUTH_PUBLIC_KEY_PEM: {}", e))?,
-                &validation,
-            )
-            .map_err(|e| format!("Invalid OAuth token: {}", e))?;
-            Ok(())
+            let data = decode::<Map<String, Value>>(&token, &key, &validation)
+                .map_err(|e| format!("Invalid OAuth token: {}", e))?;
+            Ok(Some(AuthIdentity::from_claims(
+                &data.claims,
+                config.auth_allow_unscoped_tokens,
+            )))
         }
     }
 }
@@ -248,47 +626,407 @@ fn extract_bearer_token(headers: &axum::http::HeaderMap) -> Result<String, Strin
     Ok(token.to_string())
 }
 
-async fn run_stdio_server(ctx: ToolContext) -> anyhow::Result<()> {
-    use std::io::{self, BufRead, Write};
+async fn run_stdio_server(live: LiveConfig) -> anyhow::Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let ctx = ToolContext::from_shared(live.current());
+    let in_flight = InFlightRequests::default();
+    let scheduler = RepoScheduler::default();
+    let limit = Arc::new(Semaphore::new(ctx.config.max_concurrent_requests));
+    let (line_tx, mut line_rx) = mpsc::unbounded_channel::<String>();
+    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
+
+    // Cancellations must be seen while a request is still running, so stdin is
+    // read on its own task and only everything else is queued for processing.
+    let reader_in_flight = in_flight.clone();
+    let reader = tokio::spawn(async move {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => {
+                    if reader_in_flight.try_cancel_from_message(STDIO_SCOPE, &line) {
+                        continue;
+                    }
+                    if line_tx.send(line).is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!("Error reading stdin: {}", e);
+                    break;
+                }
+            }
+        }
+    });
 
-    let stdin = io::stdin();
-    let stdout = io::stdout();
-    let mut stdout = stdout.lock();
+    // Requests finish out of order; a single writer keeps each message on its own line.
+    let writer = tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        while let Some(message) = out_rx.recv().await {
+            let written = async {
+                stdout.write_all(message.as_bytes()).await?;
+                stdout.write_all(b"\n").await?;
+                stdout.flush().await
+            };
+            if let Err(e) = written.await {
+                tracing::error!("Error writing stdout: {}", e);
+                break;
+            }
+        }
+    });
 
-    let mut stdin_lock = stdin.lock();
-    let mut line = String::new();
+    let announce_tx = out_tx.clone();
+    let announcer = announce_tool_changes(live.clone(), move || {
+        let _ = announce_tx.send(TOOLS_LIST_CHANGED.to_string());
+        std::future::ready(())
+    });
 
-    loop {
-        line.clear();
-        match stdin_lock.read_line(&mut line) {
-            Ok(0) => break,
-            Ok(_) => {
-                let response = handle_request(&ctx, &line).await;
-                writeln!(stdout, "{}", response)?;
-                stdout.flush()?;
-            }
-            Err(e) => {
-                tracing::error!("Error reading stdin: {}", e);
-                break;
+    let mut tasks = tokio::task::JoinSet::new();
+    while let Some(line) = line_rx.recv().await {
+        let message = match parse_message(&line) {
+            Ok(message) => message,
+            Err(resp) => {
+                let _ = out_tx.send(resp);
+                continue;
             }
+        };
+        let (entries, is_batch) = match message {
+            IncomingMessage::Single(req) => (vec![Ok(req)], false),
+            IncomingMessage::Batch(entries) => (entries, true),
+        };
+
+        // Every request in a batch takes its own ticket and permit, so batched
+        // calls are limited and ordered exactly like separate lines.
+        let mut calls = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let req = match entry {
+                Ok(req) => req,
+                Err(resp) => {
+                    calls.push(futures_util::future::ready(Some(resp)).boxed());
+                    continue;
+                }
+            };
+            // Tickets are handed out in arrival order, which is what orders
+            // calls on the same repository.
+            let ticket = scheduler.schedule(STDIO_SCOPE, &req);
+            let ctx = ctx
+                .clone()
+                .with_notifier(out_tx.clone())
+                .with_config(live.current());
+            let in_flight = in_flight.clone();
+            let limit = limit.clone();
+            // The permit is taken inside the task once the ticket is ready, so
+            // calls queued on a busy repository never stop this loop reading.
+            let call = tokio::spawn(async move {
+                let limit = Some(limit.as_ref());
+                if is_batch {
+                    dispatch_batch_entry(&ctx, &in_flight, STDIO_SCOPE, req, ticket, limit).await
+                } else {
+                    dispatch_request(&ctx, &in_flight, STDIO_SCOPE, req, ticket, limit).await
+                }
+            });
+            calls.push(call.map(|joined| joined.ok().flatten()).boxed());
         }
+
+        let out_tx = out_tx.clone();
+        tasks.spawn(async move {
+            let responses = futures_util::future::join_all(calls).await;
+            let response = if is_batch {
+                batch_response(responses)
+            } else {
+                responses.into_iter().flatten().next()
+            };
+            if let Some(response) = response {
+                let _ = out_tx.send(response);
+            }
+        });
+
+        while tasks.try_join_next().is_some() {}
     }
 
+    while tasks.join_next().await.is_some() {}
+    // The announcer holds a sender too; the writer stops once all are gone.
+    announcer.abort();
+    let _ = announcer.await;
+    drop(out_tx);
+    let _ = writer.await;
+    reader.abort();
     Ok(())
 }
 
-async fn handle_request(ctx: &ToolContext, input: &str) -> String {
-    let request: Result<JsonRpcRequest, _> = serde_json::from_str(input);
+/// Orders tool calls that touch the same repository: a mutating call waits for
+/// every earlier call on that repository, a read-only call only for earlier
+/// mutating ones. Everything else runs concurrently.
+///
+/// Calls without a `path` argument are keyed on the working directory of their
+/// scope, so changing it is treated as a mutation of that key.
+#[derive(Default)]
+struct RepoScheduler {
+    repos: Mutex<HashMap<RepoKey, RepoQueue>>,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum RepoKey {
+    Path(String),
+    /// The session working directory of an in-flight scope.
+    WorkingDir(String),
+}
+
+#[derive(Default)]
+struct RepoQueue {
+    last_write: Option<watch::Receiver<()>>,
+    reads: Vec<watch::Receiver<()>>,
+}
+
+/// Held by a running request; dropping it releases the requests queued behind it.
+struct RepoTicket {
+    waits: Vec<watch::Receiver<()>>,
+    _done: watch::Sender<()>,
+}
+
+impl RepoTicket {
+    async fn ready(&mut self) {
+        for wait in &mut self.waits {
+            // Nothing is ever sent; `changed` errors once the earlier request drops its ticket.
+            while wait.changed().await.is_ok() {}
+        }
+    }
+}
+
+impl RepoScheduler {
+    fn schedule(&self, scope: &str, req: &JsonRpcRequest) -> Option<RepoTicket> {
+        if req.method != "tools/call" {
+            return None;
+        }
+        let call: ToolCallParams = parse_params(req.params.as_deref()).ok()?;
+        let working_dir = || RepoKey::WorkingDir(scope.to_string());
+        let (key, mutating) = match call.name.as_str() {
+            "git_set_working_dir" | "git_clear_working_dir" => (working_dir(), true),
+            name => (
+                repo_key(&call.arguments).map_or_else(working_dir, RepoKey::Path),
+                tools::mutates_repository(name, &call.arguments),
+            ),
+        };
+
+        let (done, done_rx) = watch::channel(());
+        let mut repos = self.repos.lock().ok()?;
+        repos.retain(|_, queue| queue.prune());
+        let queue = repos.entry(key).or_default();
+
+        let mut waits: Vec<_> = queue.last_write.iter().cloned().collect();
+        if mutating {
+            waits.append(&mut queue.reads);
+            queue.last_write = Some(done_rx);
+        } else {
+            queue.reads.push(done_rx);
+        }
+
+        Some(RepoTicket { waits, _done: done })
+    }
+}
+
+impl RepoQueue {
+    /// Drops finished requests; returns whether anything is still pending.
+    fn prune(&mut self) -> bool {
+        let finished = |rx: &watch::Receiver<()>| rx.has_changed().is_err();
+        if self.last_write.as_ref().is_some_and(finished) {
+            self.last_write = None;
+        }
+        self.reads.retain(|rx| !finished(rx));
+        self.last_write.is_some() || !self.reads.is_empty()
+    }
+}
+
+fn repo_key(arguments: &Value) -> Option<String> {
+    ["path", "local_path"]
+        .iter()
+        .find_map(|key| arguments.get(*key).and_then(|v| v.as_str()))
+        .map(|p| path_clean::clean(p).display().to_string())
+}
+
+/// In-flight scope used by the stdio transport, which has a single client.
+const STDIO_SCOPE: &str = "";
+
+/// JSON-RPC error code returned for requests aborted by `notifications/cancelled`.
+const REQUEST_CANCELLED: i32 = -32800;
 
-    match request {
-        Ok(req) => {
-            let result = process_request(ctx, &req.method, req.params.as_deref()).await;
-            match result {
-                Ok(response) => json_rpc_response(req.id.as_ref(), response),
-                Err(e) => json_rpc_error(req.id.as_ref(), &e.to_string(), -32603),
+/// JSON-RPC error code for `initialize` when `MCP_MAX_SESSIONS` is reached.
+const SESSION_LIMIT_REACHED: i32 = -32000;
+
+/// Requests currently being processed, keyed by scope (the HTTP session) and
+/// JSON-RPC id, so `notifications/cancelled` can abort them.
+#[derive(Clone, Default)]
+struct InFlightRequests {
+    pending: Arc<Mutex<HashMap<String, oneshot::Sender<()>>>>,
+}
+
+impl InFlightRequests {
+    fn key(scope: &str, id: &Value) -> String {
+        format!("{}/{}", scope, id)
+    }
+
+    fn register(&self, scope: &str, id: &Value) -> oneshot::Receiver<()> {
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(Self::key(scope, id), tx);
+        }
+        rx
+    }
+
+    fn complete(&self, scope: &str, id: &Value) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&Self::key(scope, id));
+        }
+    }
+
+    fn cancel(&self, scope: &str, id: &Value) -> bool {
+        let sender = self
+            .pending
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.remove(&Self::key(scope, id)));
+        match sender {
+            Some(tx) => tx.send(()).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Cancels every in-flight request belonging to `scope`.
+    fn cancel_scope(&self, scope: &str) {
+        let prefix = format!("{}/", scope);
+        let senders: Vec<_> = match self.pending.lock() {
+            Ok(mut pending) => {
+                let keys: Vec<String> = pending
+                    .keys()
+                    .filter(|k| k.starts_with(&prefix))
+                    .cloned()
+                    .collect();
+                keys.iter().filter_map(|k| pending.remove(k)).collect()
+            }
+            Err(_) => Vec::new(),
+        };
+        for tx in senders {
+            let _ = tx.send(());
+        }
+    }
+
+    /// Handles `input` if it is a `notifications/cancelled` message.
+    fn try_cancel_from_message(&self, scope: &str, input: &str) -> bool {
+        match serde_json::from_str::<JsonRpcRequest>(input) {
+            Ok(req) if req.method == "notifications/cancelled" => {
+                self.handle_cancelled(scope, req.params.as_deref());
+                true
             }
+            _ => false,
         }
-        Err(e) => json_rpc_error(None, &format!("Parse error: {}", e), -32700),
+    }
+
+    fn handle_cancelled(&self, scope: &str, params: Option<&RawValue>) {
+        match parse_params::<CancelledParams>(params) {
+            Ok(params) => {
+                if self.cancel(scope, &params.request_id) {
+                    tracing::info!(
+                        "Cancelled request {}: {}",
+                        params.request_id,
+                        params.reason.as_deref().unwrap_or("no reason given")
+                    );
+                } else {
+                    tracing::debug!("No in-flight request {} to cancel", params.request_id);
+                }
+            }
+            Err(e) => tracing::warn!("Invalid notifications/cancelled params: {}", e),
+        }
+    }
+}
+
+async fn dispatch_request(
+    ctx: &ToolContext,
+    in_flight: &InFlightRequests,
+    scope: &str,
+    req: JsonRpcRequest,
+    mut ticket: Option<RepoTicket>,
+    limit: Option<&Semaphore>,
+) -> Option<String> {
+    if req.method == "notifications/cancelled" {
+        in_flight.handle_cancelled(scope, req.params.as_deref());
+        return None;
+    }
+
+    let Some(id) = req.id.as_ref() else {
+        handle_notification(ctx, req, ticket, limit).await;
+        return None;
+    };
+    let mut cancelled = in_flight.register(scope, id);
+
+    // Dropping the request future on cancellation also kills its git child
+    // process (see `GitExecutor`).
+    let run = async {
+        let _permit = wait_turn(ticket.as_mut(), limit).await;
+        process_request(ctx, &req.method, req.params.as_deref()).await
+    };
+    let result = tokio::select! {
+        result = run => Some(result),
+        Ok(()) = &mut cancelled => None,
+    };
+    drop(ticket);
+    in_flight.complete(scope, id);
+
+    Some(match result {
+        Some(Ok(response)) => json_rpc_response(Some(id), response),
+        Some(Err(e)) => json_rpc_error(
+            Some(id),
+            &ctx.config.redactor().redact(&e.to_string()),
+            -32603,
+        ),
+        None => json_rpc_error(Some(id), "Request cancelled", REQUEST_CANCELLED),
+    })
+}
+
+/// Waits for the repository ticket, then for a permit from `limit`. The permit
+/// comes second so that requests queued on a repository never hold one.
+async fn wait_turn<'a>(
+    ticket: Option<&mut RepoTicket>,
+    limit: Option<&'a Semaphore>,
+) -> Option<tokio::sync::SemaphorePermit<'a>> {
+    if let Some(ticket) = ticket {
+        ticket.ready().await;
+    }
+    match limit {
+        Some(limit) => limit.acquire().await.ok(),
+        None => None,
+    }
+}
+
+/// Client notifications that need no action from the server.
+const ACCEPTED_NOTIFICATIONS: &[&str] = &[
+    "notifications/initialized",
+    "notifications/progress",
+    "notifications/roots/list_changed",
+];
+
+/// Handles a message without an `id`. Per JSON-RPC 2.0 it never gets a
+/// response, not even an error.
+async fn handle_notification(
+    ctx: &ToolContext,
+    req: JsonRpcRequest,
+    mut ticket: Option<RepoTicket>,
+    limit: Option<&Semaphore>,
+) {
+    if ACCEPTED_NOTIFICATIONS.contains(&req.method.as_str()) {
+        tracing::debug!("Received {}", req.method);
+        return;
+    }
+    if req.method.starts_with("notifications/") {
+        tracing::debug!("Ignoring unsupported notification {}", req.method);
+        return;
+    }
+
+    // Any other method sent as a notification still runs; only its result is
+    // dropped.
+    let _permit = wait_turn(ticket.as_mut(), limit).await;
+    if let Err(e) = process_request(ctx, &req.method, req.params.as_deref()).await {
+        tracing::warn!("Notification {} failed: {}", req.method, e);
     }
 }
 
@@ -296,11 +1034,142 @@ async fn handle_request(ctx: &ToolContext, input: &str) -> String {
 struct JsonRpcRequest {
     #[serde(rename = "jsonrpc")]
     _jsonrpc: String,
+    /// `None` only when the member is absent, which makes this a notification;
+    /// an explicit `"id": null` is still a request.
+    #[serde(default, deserialize_with = "deserialize_present")]
     id: Option<Value>,
     method: String,
     params: Option<Box<RawValue>>,
 }
 
+fn deserialize_present<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// A line or body received from the client: one request or a JSON-RPC batch.
+enum IncomingMessage {
+    Single(JsonRpcRequest),
+    /// Entries that are not valid requests carry their error response.
+    Batch(Vec<Result<JsonRpcRequest, String>>),
+}
+
+/// Parses a single request or a batch. `Err` holds the error response for the
+/// whole message.
+fn parse_message(input: &str) -> Result<IncomingMessage, String> {
+    if !input.trim_start().starts_with('[') {
+        return serde_json::from_str::<JsonRpcRequest>(input)
+            .map(IncomingMessage::Single)
+            .map_err(|e| json_rpc_error(None, &format!("Parse error: {}", e), -32700));
+    }
+
+    let entries: Vec<Box<RawValue>> = serde_json::from_str(input)
+        .map_err(|e| json_rpc_error(None, &format!("Parse error: {}", e), -32700))?;
+    if entries.is_empty() {
+        return Err(json_rpc_error(None, "Invalid Request: empty batch", -32600));
+    }
+    Ok(IncomingMessage::Batch(
+        entries
+            .iter()
+            .map(|entry| {
+                serde_json::from_str::<JsonRpcRequest>(entry.get())
+                    .map_err(|e| json_rpc_error(None, &format!("Invalid Request: {}", e), -32600))
+            })
+            .collect(),
+    ))
+}
+
+impl IncomingMessage {
+    fn wants_progress(&self) -> bool {
+        match self {
+            IncomingMessage::Single(req) => request_progress_token(req.params.as_deref()).is_some(),
+            IncomingMessage::Batch(entries) => entries
+                .iter()
+                .flatten()
+                .any(|req| request_progress_token(req.params.as_deref()).is_some()),
+        }
+    }
+}
+
+/// Runs a single request or every entry of a batch concurrently, ordered and
+/// limited by `scheduler` and `limit` like separate stdio lines.
+async fn dispatch_message(
+    ctx: &ToolContext,
+    in_flight: &InFlightRequests,
+    scheduler: &RepoScheduler,
+    limit: &Semaphore,
+    scope: &str,
+    message: IncomingMessage,
+) -> Option<String> {
+    match message {
+        IncomingMessage::Single(req) => {
+            let ticket = scheduler.schedule(scope, &req);
+            dispatch_request(ctx, in_flight, scope, req, ticket, Some(limit)).await
+        }
+        IncomingMessage::Batch(entries) => {
+            // Tickets are taken before anything runs, in batch order.
+            let responses: Vec<_> = entries
+                .into_iter()
+                .map(|entry| {
+                    let entry = entry.map(|req| {
+                        let ticket = scheduler.schedule(scope, &req);
+                        (req, ticket)
+                    });
+                    async move {
+                        match entry {
+                            Ok((req, ticket)) => {
+                                dispatch_batch_entry(
+                                    ctx,
+                                    in_flight,
+                                    scope,
+                                    req,
+                                    ticket,
+                                    Some(limit),
+                                )
+                                .await
+                            }
+                            Err(resp) => Some(resp),
+                        }
+                    }
+                })
+                .collect();
+            batch_response(futures_util::future::join_all(responses).await)
+        }
+    }
+}
+
+/// Like `dispatch_request`, but `initialize` may not be batched.
+async fn dispatch_batch_entry(
+    ctx: &ToolContext,
+    in_flight: &InFlightRequests,
+    scope: &str,
+    req: JsonRpcRequest,
+    ticket: Option<RepoTicket>,
+    limit: Option<&Semaphore>,
+) -> Option<String> {
+    if req.method == "initialize" && req.id.is_some() {
+        return Some(json_rpc_error(
+            req.id.as_ref(),
+            "Invalid Request: initialize cannot be part of a batch",
+            -32600,
+        ));
+    }
+    dispatch_request(ctx, in_flight, scope, req, ticket, limit).await
+}
+
+/// Combines the responses to a batch. Notifications contribute nothing, and a
+/// batch of only notifications gets no response at all.
+fn batch_response(responses: Vec<Option<String>>) -> Option<String> {
+    let responses: Vec<String> = responses.into_iter().flatten().collect();
+    if responses.is_empty() {
+        None
+    } else {
+        Some(format!("[{}]", responses.join(",")))
+    }
+}
+
 #[derive(Serialize)]
 struct JsonRpcResponse<'a> {
     jsonrpc: &'static str,
@@ -368,6 +1237,34 @@ struct ToolCallParams {
     arguments: Value,
 }
 
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct RequestMeta {
+    #[serde(default)]
+    progress_token: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct RequestParamsMeta {
+    #[serde(rename = "_meta", default)]
+    meta: RequestMeta,
+}
+
+fn request_progress_token(params: Option<&RawValue>) -> Option<Value> {
+    parse_params::<RequestParamsMeta>(params)
+        .ok()?
+        .meta
+        .progress_token
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelledParams {
+    request_id: Value,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
 #[derive(Deserialize)]
 struct ResourceReadParams {
     uri: String,
@@ -394,7 +1291,9 @@ async fn process_request(
         "initialize" => Ok(serde_json::json!({
             "protocolVersion": "2025-11-25",
             "capabilities": {
-                "tools": {},
+                "tools": {
+                    "listChanged": true
+                },
                 "resources": {
                     "subscribe": false,
                     "listChanged": false
@@ -410,15 +1309,24 @@ async fn process_request(
         })),
 
         "tools/list" => Ok(serde_json::json!({
-            "tools": get_tool_definitions()
+            "tools": list_tools(ctx)
         })),
 
         "tools/call" => {
             let parsed: ToolCallParams = parse_params(params)?;
 
+            let ctx = &match (request_progress_token(params), ctx.notifier.clone()) {
+                (Some(token), Some(notifier)) => ctx.clone().with_progress(progress_notifier(
+                    token,
+                    notifier,
+                    ctx.config.redactor(),
+                )),
+                _ => ctx.clone(),
+            };
+
             match execute_tool(ctx, &parsed.name, parsed.arguments).await {
                 Ok(value) => Ok(call_tool_ok(ctx, value)),
-                Err(e) => Ok(call_tool_error(e.to_string())),
+                Err(e) => Ok(call_tool_error(ctx, e.to_string())),
             }
         }
 
@@ -540,7 +1448,50 @@ async fn process_request(
     }
 }
 
+/// Forwards git progress as `notifications/progress` for `token`. git runs
+/// several phases that each restart at 0%, so the reported value is offset by
+/// the phase count to keep it increasing as MCP requires.
+fn progress_notifier(token: Value, notifier: Notifier, redactor: Redactor) -> ProgressSink {
+    #[derive(Default)]
+    struct PhaseState {
+        phase: String,
+        phases: u32,
+        last: Option<f64>,
+    }
+
+    let state = Mutex::new(PhaseState::default());
+    Arc::new(move |update: &GitProgress| {
+        let Ok(mut state) = state.lock() else {
+            return;
+        };
+        if state.phase != update.phase {
+            state.phase = update.phase.clone();
+            state.phases += 1;
+        }
+        let progress = f64::from(state.phases.saturating_sub(1)) * 100.0
+            + f64::from(update.percent.unwrap_or(0));
+        if state.last.is_some_and(|last| progress <= last) {
+            return;
+        }
+        state.last = Some(progress);
+
+        let notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": {
+                "progressToken": token,
+                "progress": progress,
+                "message": redactor.redact(&update.line)
+            }
+        });
+        let _ = notifier.send(notification.to_string());
+    })
+}
+
+/// Tool results and errors are redacted before they reach the client: git
+/// output can carry remote URLs with credentials or keys committed by mistake.
 fn call_tool_ok(ctx: &ToolContext, value: Value) -> Value {
+    let value = ctx.config.redactor().redact_value(value);
     let text = RESPONSE_BUFFER.with(|buffer| {
         let mut buf = buffer.borrow_mut();
         buf.clear();
@@ -565,88 +1516,352 @@ fn call_tool_ok(ctx: &ToolContext, value: Value) -> Value {
             "type": "text",
             "text": text
         }],
+        "structuredContent": value,
         "isError": false
     })
 }
 
-fn call_tool_error(message: String) -> Value {
+fn call_tool_error(ctx: &ToolContext, message: String) -> Value {
     serde_json::json!({
         "content": [{
             "type": "text",
-            "text": message
+            "text": ctx.config.redactor().redact(&message)
         }],
         "isError": true
     })
 }
 
+/// Whether this server has a tool named `name`, under any configuration.
+pub(crate) fn is_tool(name: &str) -> bool {
+    get_tool_definitions()
+        .iter()
+        .any(|tool| tool["name"].as_str() == Some(name))
+}
+
 fn get_tool_definitions() -> Vec<Value> {
+    use ToolHints as H;
+
     vec![
-        tool_def::<repo::GitStatusInput>("git_status", "Show the working tree status"),
-        tool_def::<repo::GitInitInput>("git_init", "Initialize a new Git repository"),
-        tool_def::<repo::GitCloneInput>("git_clone", "Clone a repository from a remote URL"),
-        tool_def::<repo::GitCleanInput>(
+        tool_def::<repo::GitStatusInput, repo::GitStatusOutput>(
+            "git_status",
+            "Git Status",
+            "Show the working tree status",
+            H::READ_ONLY,
+        ),
+        tool_def::<repo::GitInitInput, repo::GitInitOutput>(
+            "git_init",
+            "Git Init",
+            "Initialize a new Git repository",
+            H::write(false, true),
+        ),
+        tool_def::<repo::GitCloneInput, repo::GitCloneOutput>(
+            "git_clone",
+            "Git Clone",
+            "Clone a repository from a remote URL",
+            H::write(false, false).open_world(),
+        ),
+        tool_def::<repo::GitCleanInput, repo::GitCleanOutput>(
             "git_clean",
+            "Git Clean",
             "Remove untracked files from the working tree",
+            H::write(true, true),
+        ),
+        tool_def::<staging::GitAddInput, staging::GitAddOutput>(
+            "git_add",
+            "Git Add",
+            "Stage files for commit",
+            H::write(false, true),
+        ),
+        tool_def::<staging::GitCommitInput, staging::GitCommitOutput>(
+            "git_commit",
+            "Git Commit",
+            "Create a new commit",
+            H::write(false, false),
+        ),
+        tool_def::<staging::GitDiffInput, staging::GitDiffOutput>(
+            "git_diff",
+            "Git Diff",
+            "View differences",
+            H::READ_ONLY,
         ),
-        tool_def::<staging::GitAddInput>("git_add", "Stage files for commit"),
-        tool_def::<staging::GitCommitInput>("git_commit", "Create a new commit"),
-        tool_def::<staging::GitDiffInput>("git_diff", "View differences"),
-        tool_def::<history::GitLogInput>("git_log", "View commit history"),
-        tool_def::<history::GitShowInput>("git_show", "Show details of a git object"),
-        tool_def::<history::GitBlameInput>("git_blame", "Show line-by-line authorship"),
-        tool_def::<history::GitReflogInput>("git_reflog", "View the reference logs"),
-        tool_def::<branching::GitBranchInput>("git_branch", "Manage branches"),
-        tool_def::<branching::GitCheckoutInput>(
+        tool_def::<history::GitLogInput, history::GitLogOutput>(
+            "git_log",
+            "Git Log",
+            "View commit history",
+            H::READ_ONLY,
+        ),
+        tool_def::<history::GitShowInput, history::GitShowOutput>(
+            "git_show",
+            "Git Show",
+            "Show details of a git object",
+            H::READ_ONLY,
+        ),
+        tool_def::<history::GitBlameInput, history::GitBlameOutput>(
+            "git_blame",
+            "Git Blame",
+            "Show line-by-line authorship",
+            H::READ_ONLY,
+        ),
+        tool_def::<history::GitReflogInput, history::GitReflogOutput>(
+            "git_reflog",
+            "Git Reflog",
+            "View the reference logs",
+            H::READ_ONLY,
+        ),
+        tool_def::<history::GitVerifyInput, history::GitVerifyOutput>(
+            "git_verify",
+            "Git Verify",
+            "Verify the signatures of commits, tags or commit ranges: status, signer, key fingerprint and trust",
+            H::READ_ONLY,
+        ),
+        tool_def::<branching::GitBranchInput, branching::GitBranchOutput>(
+            "git_branch",
+            "Git Branch",
+            "Manage branches. list and show-current are read-only; create, delete and rename change the repository",
+            H::write(true, false),
+        ),
+        tool_def::<branching::GitCheckoutInput, branching::GitCheckoutOutput>(
             "git_checkout",
+            "Git Checkout",
             "Switch branches or restore working tree files",
+            H::write(true, true),
+        ),
+        tool_def::<branching::GitMergeInput, branching::GitMergeOutput>(
+            "git_merge",
+            "Git Merge",
+            "Merge branches together",
+            H::write(false, false),
+        ),
+        tool_def::<branching::GitRebaseInput, branching::GitRebaseOutput>(
+            "git_rebase",
+            "Git Rebase",
+            "Rebase commits onto another branch",
+            H::write(true, false),
+        ),
+        tool_def::<branching::GitCherryPickInput, branching::GitCherryPickOutput>(
+            "git_cherry_pick",
+            "Git Cherry-pick",
+            "Cherry-pick commits",
+            H::write(false, false),
         ),
-        tool_def::<branching::GitMergeInput>("git_merge", "Merge branches together"),
-        tool_def::<branching::GitRebaseInput>("git_rebase", "Rebase commits onto another branch"),
-        tool_def::<branching::GitCherryPickInput>("git_cherry_pick", "Cherry-pick commits"),
-        tool_def::<remote::GitRemoteInput>("git_remote", "Manage remote repositories"),
-        tool_def::<remote::GitFetchInput>("git_fetch", "Fetch updates from a remote repository"),
-        tool_def::<remote::GitPullInput>("git_pull", "Pull changes from a remote repository"),
-        tool_def::<remote::GitPushInput>("git_push", "Push changes to a remote repository"),
-        tool_def::<advanced::GitTagInput>("git_tag", "Manage tags"),
-        tool_def::<advanced::GitStashInput>("git_stash", "Manage stashes"),
-        tool_def::<advanced::GitResetInput>("git_reset", "Reset current HEAD to specified state"),
-        tool_def::<advanced::GitWorktreeInput>("git_worktree", "Manage multiple working trees"),
-        tool_def::<advanced::GitSetWorkingDirInput>(
+        tool_def::<remote::GitRemoteInput, remote::GitRemoteOutput>(
+            "git_remote",
+            "Git Remote",
+            "Manage remote repositories. list and get-url are read-only; add, remove, rename and set-url change the repository",
+            H::write(true, false),
+        ),
+        tool_def::<remote::GitFetchInput, remote::GitFetchOutput>(
+            "git_fetch",
+            "Git Fetch",
+            "Fetch updates from a remote repository",
+            H::write(false, true).open_world(),
+        ),
+        tool_def::<remote::GitPullInput, remote::GitPullOutput>(
+            "git_pull",
+            "Git Pull",
+            "Pull changes from a remote repository",
+            H::write(false, false).open_world(),
+        ),
+        tool_def::<remote::GitPushInput, remote::GitPushOutput>(
+            "git_push",
+            "Git Push",
+            "Push changes to a remote repository. dry_run only reports what would be pushed",
+            H::write(true, false).open_world(),
+        ),
+        tool_def::<advanced::GitTagInput, advanced::GitTagOutput>(
+            "git_tag",
+            "Git Tag",
+            "Manage tags. list is read-only; create and delete change the repository",
+            H::write(true, false),
+        ),
+        tool_def::<advanced::GitStashInput, advanced::GitStashOutput>(
+            "git_stash",
+            "Git Stash",
+            "Manage stashes. list is read-only; push, pop, apply, drop and clear change the repository, and drop and clear discard stashed work",
+            H::write(true, false),
+        ),
+        tool_def::<advanced::GitResetInput, advanced::GitResetOutput>(
+            "git_reset",
+            "Git Reset",
+            "Reset current HEAD to specified state",
+            H::write(true, false),
+        ),
+        tool_def::<advanced::GitWorktreeInput, advanced::GitWorktreeOutput>(
+            "git_worktree",
+            "Git Worktree",
+            "Manage multiple working trees. list is read-only; add, remove and prune change the repository",
+            H::write(true, false),
+        ),
+        tool_def::<advanced::GitSetWorkingDirInput, advanced::GitSetWorkingDirOutput>(
             "git_set_working_dir",
+            "Set Working Directory",
             "Set the session working directory",
+            H::READ_ONLY,
         ),
-        tool_def::<advanced::GitClearWorkingDirInput>(
+        tool_def::<advanced::GitClearWorkingDirInput, advanced::GitClearWorkingDirOutput>(
             "git_clear_working_dir",
+            "Clear Working Directory",
             "Clear the session working directory",
+            H::READ_ONLY,
         ),
-        tool_def::<analysis::GitChangelogAnalyzeInput>(
+        tool_def::<analysis::GitChangelogAnalyzeInput, analysis::GitChangelogAnalyzeOutput>(
             "git_changelog_analyze",
+            "Analyze Changelog",
             "Gather git context for changelog analysis",
+            H::READ_ONLY,
         ),
-        tool_def::<advanced::GitWrapupInstructionsInput>(
+        tool_def::<advanced::GitWrapupInstructionsInput, advanced::GitWrapupInstructionsOutput>(
             "git_wrapup_instructions",
+            "Wrap-up Instructions",
             "Get git wrap-up workflow instructions",
+            H::READ_ONLY,
         ),
     ]
 }
 
-fn tool_def<T: JsonSchema>(name: &str, description: &str) -> Value {
-    let schema = schema_for!(T);
-    let input_schema = serde_json::to_value(&schema.schema)
-        .unwrap_or_else(|_| serde_json::json!({"type": "object"}));
+/// MCP tool annotations. Tools whose effect depends on a mode argument are
+/// annotated for their most far-reaching mode; the description names the
+/// read-only modes.
+#[derive(Clone, Copy)]
+struct ToolHints {
+    read_only: bool,
+    destructive: bool,
+    idempotent: bool,
+    open_world: bool,
+}
 
+impl ToolHints {
+    /// Only reads the repository or session state.
+    const READ_ONLY: Self = Self {
+        read_only: true,
+        destructive: false,
+        idempotent: true,
+        open_world: false,
+    };
+
+    /// Changes the repository; `destructive` if it can discard work or history.
+    const fn write(destructive: bool, idempotent: bool) -> Self {
+        Self {
+            read_only: false,
+            destructive,
+            idempotent,
+            open_world: false,
+        }
+    }
+
+    /// Talks to a remote repository.
+    const fn open_world(self) -> Self {
+        Self {
+            open_world: true,
+            ..self
+        }
+    }
+}
+
+fn tool_def<I: JsonSchema, O: JsonSchema>(
+    name: &str,
+    title: &str,
+    description: &str,
+    hints: ToolHints,
+) -> Value {
     serde_json::json!({
         "name": name,
+        "title": title,
         "description": description,
-        "inputSchema": input_schema
+        "inputSchema": object_schema::<I>(),
+        "outputSchema": object_schema::<O>(),
+        "annotations": {
+            "title": title,
+            "readOnlyHint": hints.read_only,
+            "destructiveHint": hints.destructive,
+            "idempotentHint": hints.idempotent,
+            "openWorldHint": hints.open_world
+        }
     })
 }
 
+/// Schema for `T` with nested types inlined, since clients resolve neither
+/// `definitions` nor `$ref`.
+fn object_schema<T: JsonSchema>() -> Value {
+    let schema = SchemaSettings::draft07()
+        .with(|s| s.inline_subschemas = true)
+        .into_generator()
+        .into_root_schema_for::<T>();
+    serde_json::to_value(&schema.schema).unwrap_or_else(|_| serde_json::json!({"type": "object"}))
+}
+
+/// The tools this server offers under its current configuration.
+fn list_tools(ctx: &ToolContext) -> Vec<Value> {
+    get_tool_definitions()
+        .into_iter()
+        .filter(|tool| {
+            let name = tool["name"].as_str().unwrap_or_default();
+            offers_tool(&ctx.config, name)
+                && ctx
+                    .identity
+                    .as_ref()
+                    .is_none_or(|identity| identity.may_use_tool(name))
+        })
+        .collect()
+}
+
+/// Whether `config` exposes a tool at all; tokens may narrow this further.
+fn offers_tool(config: &Config, name: &str) -> bool {
+    (!config.read_only || tools::available_in_read_only(name)) && config.policy.allows_tool(name)
+}
+
+/// Names of the tools `config` exposes, to tell when a reload changes them.
+fn offered_tools(config: &Config) -> Vec<String> {
+    get_tool_definitions()
+        .iter()
+        .filter_map(|tool| tool["name"].as_str())
+        .filter(|name| offers_tool(config, name))
+        .map(str::to_string)
+        .collect()
+}
+
 pub async fn execute_tool(
     ctx: &ToolContext,
     name: &str,
     arguments: Value,
 ) -> anyhow::Result<Value> {
+    // Git commands, including those of the checks, run under the
+    // configuration of this request even if the session's executor is older.
+    with_command_config(ctx.config.clone(), checked_tool(ctx, name, arguments)).await
+}
+
+async fn checked_tool(ctx: &ToolContext, name: &str, arguments: Value) -> anyhow::Result<Value> {
+    if ctx.config.read_only
+        && (!tools::available_in_read_only(name) || tools::mutates_repository(name, &arguments))
+    {
+        return Err(GitMcpError::PermissionDenied(format!(
+            "{} is not available: the server is read-only (MCP_READ_ONLY)",
+            name
+        ))
+        .into());
+    }
+    if let Some(identity) = &ctx.identity {
+        identity.authorize(ctx, name, &arguments).await?;
+    }
+    ctx.config.policy.check(name, &arguments)?;
+    policy::check_protected_refs(ctx, name, &arguments).await?;
+
+    let timeout = ctx.config.tool_timeout(name);
+    // The dispatch future holds the state of every tool; boxing it keeps it
+    // off the worker's stack.
+    let call = with_command_timeout(
+        timeout,
+        Box::pin(dispatch_tool(ctx, name, arguments.clone())),
+    );
+    match &ctx.config.audit {
+        Some(log) if tools::mutates_repository(name, &arguments) => {
+            audit::audited(log, ctx, name, &arguments, call).await
+        }
+        _ => call.await,
+    }
+}
+
+async fn dispatch_tool(ctx: &ToolContext, name: &str, arguments: Value) -> anyhow::Result<Value> {
     match name {
         "git_status" => {
             let input: repo::GitStatusInput = serde_json::from_value(arguments)?;
@@ -703,6 +1918,11 @@ pub async fn execute_tool(
             let result = history::git_reflog(ctx.clone(), input).await?;
             Ok(serde_json::to_value(result)?)
         }
+        "git_verify" => {
+            let input: history::GitVerifyInput = serde_json::from_value(arguments)?;
+            let result = history::git_verify(ctx.clone(), input).await?;
+            Ok(serde_json::to_value(result)?)
+        }
         "git_branch" => {
             let input: branching::GitBranchInput = serde_json::from_value(arguments)?;
             let result = branching::git_branch(ctx.clone(), input).await?;
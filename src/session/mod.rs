@@ -0,0 +1,360 @@
+use crate::config::{Config, LiveConfig};
+use crate::error::{GitMcpError, Result};
+use crate::tools::ToolContext;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+use uuid::Uuid;
+
+/// Messages buffered per GET stream before a slow client starts missing them.
+const SESSION_EVENT_BUFFER: usize = 256;
+
+/// File under `MCP_STATE_DIR` that sessions are persisted to.
+const SESSIONS_FILE: &str = "sessions.json";
+
+/// Requests that change nothing but the sessions' last use rewrite the state
+/// file at most this often. A restored session may expire this much early.
+const LAST_SEEN_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// State for one HTTP session. Messages sent through the session's notifier
+/// that are not tied to an SSE response reach the client on its GET stream.
+#[derive(Clone)]
+pub struct Session {
+    pub ctx: ToolContext,
+    pub events: broadcast::Sender<String>,
+    created_at: SystemTime,
+    last_seen: Instant,
+}
+
+impl Session {
+    fn new(id: &str, config: Arc<Config>) -> Self {
+        let (events, _) = broadcast::channel(SESSION_EVENT_BUFFER);
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        let forward = events.clone();
+        tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                // No open GET stream means nobody is listening; drop it.
+                let _ = forward.send(message);
+            }
+        });
+        Self {
+            ctx: ToolContext::from_shared(config)
+                .with_notifier(tx)
+                .with_session_id(id.to_string()),
+            events,
+            created_at: SystemTime::now(),
+            last_seen: Instant::now(),
+        }
+    }
+
+    /// A request holding a clone of the context is still running.
+    fn is_busy(&self) -> bool {
+        Arc::strong_count(&self.ctx.executor) > 1
+    }
+}
+
+/// On-disk form of a session. Times are Unix timestamps in seconds.
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionRecord {
+    id: String,
+    working_dir: Option<PathBuf>,
+    created_at: u64,
+    last_seen: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionFile {
+    sessions: Vec<SessionRecord>,
+}
+
+/// What the state file last received, to tell whether a request changed
+/// anything worth writing.
+#[derive(Default)]
+struct SavedState {
+    working_dirs: HashMap<String, Option<PathBuf>>,
+    at: Option<Instant>,
+}
+
+/// Live HTTP sessions, bounded by `MCP_MAX_SESSIONS` and expired after
+/// `MCP_SESSION_IDLE_TIMEOUT_SECS` without requests. When `MCP_STATE_DIR` is
+/// set, sessions and their working directories survive restarts.
+pub struct SessionStore {
+    config: LiveConfig,
+    sessions: RwLock<HashMap<String, Session>>,
+    /// Held while the state file is written.
+    saved: Mutex<SavedState>,
+}
+
+impl SessionStore {
+    pub fn new(config: LiveConfig) -> Self {
+        Self {
+            config,
+            sessions: RwLock::new(HashMap::new()),
+            saved: Mutex::new(SavedState::default()),
+        }
+    }
+
+    fn state_file(&self) -> Option<PathBuf> {
+        self.config
+            .current()
+            .state_dir
+            .as_ref()
+            .map(|dir| dir.join(SESSIONS_FILE))
+    }
+
+    pub async fn create(&self) -> Result<(String, Session)> {
+        let config = self.config.current();
+        let (id, session) = {
+            let mut sessions = self.sessions.write().await;
+            if sessions.len() >= config.max_sessions {
+                return Err(GitMcpError::InvalidState(format!(
+                    "Session limit reached ({} active sessions); terminate an existing session or retry later",
+                    sessions.len()
+                )));
+            }
+            let id = Uuid::new_v4().to_string();
+            let session = Session::new(&id, config);
+            sessions.insert(id.clone(), session.clone());
+            tracing::info!("Created session {} ({} active)", id, sessions.len());
+            (id, session)
+        };
+        self.save().await;
+        Ok((id, session))
+    }
+
+    /// Looks up a session and marks it as used.
+    pub async fn get(&self, id: &str) -> Option<Session> {
+        let mut sessions = self.sessions.write().await;
+        let session = sessions.get_mut(id)?;
+        session.last_seen = Instant::now();
+        Some(session.clone())
+    }
+
+    /// Removes a session. Its executor is freed once in-flight requests finish,
+    /// and its GET streams end.
+    pub async fn remove(&self, id: &str) -> bool {
+        let removed = {
+            let mut sessions = self.sessions.write().await;
+            let removed = sessions.remove(id).is_some();
+            if removed {
+                tracing::info!("Terminated session {} ({} active)", id, sessions.len());
+            }
+            removed
+        };
+        if removed {
+            self.save().await;
+        }
+        removed
+    }
+
+    /// Drops sessions idle for longer than the configured timeout, skipping
+    /// those with requests still running.
+    pub async fn evict_idle(&self) -> Vec<String> {
+        let timeout = self.config.current().session_idle_timeout;
+        if timeout.is_zero() {
+            return Vec::new();
+        }
+        let expired: Vec<String> = {
+            let mut sessions = self.sessions.write().await;
+            let expired: Vec<String> = sessions
+                .iter()
+                .filter(|(_, s)| s.last_seen.elapsed() >= timeout && !s.is_busy())
+                .map(|(id, _)| id.clone())
+                .collect();
+            for id in &expired {
+                sessions.remove(id);
+            }
+            if !expired.is_empty() {
+                tracing::info!(
+                    "Expired {} idle sessions ({} active)",
+                    expired.len(),
+                    sessions.len()
+                );
+            }
+            expired
+        };
+        if !expired.is_empty() {
+            self.save().await;
+        }
+        expired
+    }
+
+    /// How often to check for idle sessions. Without a timeout this is
+    /// still checked once a minute, as a reload may set one.
+    pub fn sweep_interval(&self) -> Duration {
+        let timeout = self.config.current().session_idle_timeout;
+        if timeout.is_zero() {
+            return Duration::from_secs(60);
+        }
+        (timeout / 4).clamp(Duration::from_secs(1), Duration::from_secs(60))
+    }
+
+    /// Sends a server-initiated message to the GET streams of every session.
+    pub async fn notify_all(&self, message: &str) {
+        for session in self.sessions.read().await.values() {
+            let _ = session.events.send(message.to_string());
+        }
+    }
+
+    /// Saves the sessions after a request in session `id` if it changed its
+    /// working directory, or if the last-use times on disk are stale.
+    pub async fn save_after_request(&self, id: &str) {
+        if self.state_file().is_none() {
+            return;
+        }
+        let Some(session) = self.sessions.read().await.get(id).cloned() else {
+            return;
+        };
+        let working_dir = session.ctx.executor.read().await.get_working_dir().cloned();
+        let due = {
+            let saved = self.saved.lock().await;
+            saved.working_dirs.get(id) != Some(&working_dir)
+                || saved
+                    .at
+                    .is_none_or(|at| at.elapsed() >= LAST_SEEN_SAVE_INTERVAL)
+        };
+        if due {
+            self.save().await;
+        }
+    }
+
+    /// Writes every session to the state file, if one is configured. Failures
+    /// are logged rather than surfaced; persistence is best effort.
+    pub async fn save(&self) {
+        let Some(path) = self.state_file() else {
+            return;
+        };
+        let mut saved = self.saved.lock().await;
+
+        let snapshot: Vec<(String, Session)> = self
+            .sessions
+            .read()
+            .await
+            .iter()
+            .map(|(id, s)| (id.clone(), s.clone()))
+            .collect();
+        let mut file = SessionFile::default();
+        for (id, session) in snapshot {
+            let working_dir = session.ctx.executor.read().await.get_working_dir().cloned();
+            let last_seen = SystemTime::now()
+                .checked_sub(session.last_seen.elapsed())
+                .unwrap_or(UNIX_EPOCH);
+            file.sessions.push(SessionRecord {
+                id,
+                working_dir,
+                created_at: unix_secs(session.created_at),
+                last_seen: unix_secs(last_seen),
+            });
+        }
+
+        match write_atomically(&path, &file).await {
+            Ok(()) => {
+                *saved = SavedState {
+                    working_dirs: file
+                        .sessions
+                        .into_iter()
+                        .map(|r| (r.id, r.working_dir))
+                        .collect(),
+                    at: Some(Instant::now()),
+                };
+            }
+            Err(e) => tracing::warn!("Failed to save sessions to {}: {}", path.display(), e),
+        }
+    }
+
+    /// Restores sessions saved by a previous run with their remaining idle
+    /// time. Sessions that expired while the server was down are dropped.
+    pub async fn load(&self) {
+        let Some(path) = self.state_file() else {
+            return;
+        };
+        let contents = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+            Err(e) => {
+                tracing::warn!("Failed to read sessions from {}: {}", path.display(), e);
+                return;
+            }
+        };
+        let mut file: SessionFile = match serde_json::from_str(&contents) {
+            Ok(file) => file,
+            Err(e) => {
+                tracing::warn!("Ignoring invalid session file {}: {}", path.display(), e);
+                return;
+            }
+        };
+
+        // Keep the most recently used sessions if the limit has since shrunk.
+        file.sessions
+            .sort_by_key(|r| std::cmp::Reverse(r.last_seen));
+        let config = self.config.current();
+        let timeout = config.session_idle_timeout;
+        let now = SystemTime::now();
+        let restored = {
+            let mut sessions = self.sessions.write().await;
+            for record in file.sessions {
+                if sessions.len() >= config.max_sessions {
+                    break;
+                }
+                let idle = now
+                    .duration_since(UNIX_EPOCH + Duration::from_secs(record.last_seen))
+                    .unwrap_or_default();
+                if !timeout.is_zero() && idle >= timeout {
+                    continue;
+                }
+
+                let mut session = Session::new(&record.id, config.clone());
+                session.created_at = UNIX_EPOCH + Duration::from_secs(record.created_at);
+                session.last_seen = Instant::now()
+                    .checked_sub(idle)
+                    .unwrap_or_else(Instant::now);
+                if let Some(dir) = record.working_dir {
+                    if let Err(e) = session.ctx.executor.write().await.set_working_dir(dir) {
+                        tracing::warn!(
+                            "Restoring session {} without its working directory: {}",
+                            record.id,
+                            e
+                        );
+                    }
+                }
+                sessions.insert(record.id, session);
+            }
+            sessions.len()
+        };
+        tracing::info!("Restored {} sessions from {}", restored, path.display());
+        self.save().await;
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+async fn write_atomically(path: &Path, file: &SessionFile) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    // Session ids are bearer credentials, so the file is only readable by the
+    // server's user. A leftover temp file may have been created with other
+    // permissions.
+    match tokio::fs::remove_file(&tmp).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut out = options.open(&tmp).await?;
+    out.write_all(&serde_json::to_vec_pretty(file)?).await?;
+    out.flush().await?;
+    drop(out);
+    tokio::fs::rename(&tmp, path).await
+}
@@ -1,4 +1,5 @@
-use crate::error::Result;
+use crate::error::{GitMcpError, Result};
+use crate::git::{ensure_signing_key, object_signature, signing_error, SignatureInfo};
 use crate::tools::repo;
 use crate::tools::ToolContext;
 use schemars::JsonSchema;
@@ -22,7 +23,9 @@ pub struct GitTagInput {
     #[schemars(description = "Tag message")]
     pub message: Option<String>,
 
-    #[schemars(description = "Create annotated tag")]
+    #[schemars(
+        description = "Create annotated tag. With commit signing enabled, created tags are always signed and annotated, and false is rejected"
+    )]
     pub annotated: Option<bool>,
 
     #[schemars(description = "Force tag creation")]
@@ -41,6 +44,8 @@ pub struct GitTagInfo {
 pub struct GitTagOutput {
     pub success: bool,
     pub tags: Vec<GitTagInfo>,
+    /// Signature of the tag just created.
+    pub signature: Option<SignatureInfo>,
     pub message: String,
 }
 
@@ -51,13 +56,30 @@ pub async fn git_tag(ctx: ToolContext, input: GitTagInput) -> Result<GitTagOutpu
 
     match input.mode.as_deref() {
         Some("create") => {
+            let sign = ctx.config.git_sign_commits;
+            if sign && input.annotated == Some(false) {
+                return Err(GitMcpError::InvalidInput(
+                    "Lightweight tags cannot be signed; GIT_SIGN_COMMITS makes every created tag a signed annotated tag".to_string(),
+                ));
+            }
+            if sign {
+                ensure_signing_key(&executor, path.as_deref()).await?;
+            }
+
             let mut args = vec!["tag"];
 
-            if input.annotated.unwrap_or(false) || input.message.is_some() {
+            // Signed tags are always annotated.
+            if sign {
+                args.push("-s");
+            } else if input.annotated.unwrap_or(false) || input.message.is_some() {
                 args.push("-a");
             }
 
-            if let Some(msg) = &input.message {
+            if let Some(msg) = input
+                .message
+                .as_ref()
+                .or(input.tag_name.as_ref().filter(|_| sign))
+            {
                 args.push("-m");
                 args.push(msg);
             }
@@ -75,15 +97,27 @@ pub async fn git_tag(ctx: ToolContext, input: GitTagInput) -> Result<GitTagOutpu
             }
 
             if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?;
+                executor.execute_in_dir(p, &args).await
             } else {
-                executor.execute(&args)?;
+                executor.execute(&args).await
             }
+            .map_err(signing_error)?;
+
+            let tag_name = input.tag_name.unwrap_or_default();
+            let signature = object_signature(&executor, path.as_deref(), "tag", &tag_name).await;
 
             Ok(GitTagOutput {
                 success: true,
                 tags: vec![],
-                message: format!("Created tag: {}", input.tag_name.unwrap_or_default()),
+                signature: Some(signature),
+                message: match (sign, input.message.is_some()) {
+                    (false, _) => format!("Created tag: {}", tag_name),
+                    (true, true) => format!("Created signed annotated tag: {}", tag_name),
+                    (true, false) => format!(
+                        "Created signed annotated tag: {} (signing requires an annotated tag; its message is the tag name)",
+                        tag_name
+                    ),
+                },
             })
         }
         Some("delete") => {
@@ -92,34 +126,36 @@ pub async fn git_tag(ctx: ToolContext, input: GitTagInput) -> Result<GitTagOutpu
                 args.push(name);
             }
             if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?;
+                executor.execute_in_dir(p, &args).await?;
             } else {
-                executor.execute(&args)?;
+                executor.execute(&args).await?;
             }
 
             Ok(GitTagOutput {
                 success: true,
                 tags: vec![],
+                signature: None,
                 message: format!("Deleted tag: {}", input.tag_name.unwrap_or_default()),
             })
         }
         _ => {
-            let output = if let Some(ref p) = path {
-                executor.execute_in_dir(
+            let output =
+                if let Some(ref p) = path {
+                    executor.execute_in_dir(
                     p,
                     &[
                         "tag",
                         "-l",
                         "--format=%(refname:short)|%(objectname:short)|%(subject)|%(taggername)",
                     ],
-                )?
-            } else {
-                executor.execute(&[
+                ).await?
+                } else {
+                    executor.execute(&[
                     "tag",
                     "-l",
                     "--format=%(refname:short)|%(objectname:short)|%(subject)|%(taggername)",
-                ])?
-            };
+                ]).await?
+                };
 
             let tags: Vec<GitTagInfo> = output
                 .stdout
@@ -149,6 +185,7 @@ pub async fn git_tag(ctx: ToolContext, input: GitTagInput) -> Result<GitTagOutpu
             Ok(GitTagOutput {
                 success: true,
                 tags,
+                signature: None,
                 message: String::new(),
             })
         }
@@ -213,9 +250,9 @@ pub async fn git_stash(ctx: ToolContext, input: GitStashInput) -> Result<GitStas
             }
 
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?
+                executor.execute_in_dir(p, &args).await?
             } else {
-                executor.execute(&args)?
+                executor.execute(&args).await?
             };
 
             Ok(GitStashOutput {
@@ -230,9 +267,9 @@ pub async fn git_stash(ctx: ToolContext, input: GitStashInput) -> Result<GitStas
                 args.push(r);
             }
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?
+                executor.execute_in_dir(p, &args).await?
             } else {
-                executor.execute(&args)?
+                executor.execute(&args).await?
             };
 
             Ok(GitStashOutput {
@@ -247,9 +284,9 @@ pub async fn git_stash(ctx: ToolContext, input: GitStashInput) -> Result<GitStas
                 args.push(r);
             }
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?
+                executor.execute_in_dir(p, &args).await?
             } else {
-                executor.execute(&args)?
+                executor.execute(&args).await?
             };
 
             Ok(GitStashOutput {
@@ -264,9 +301,9 @@ pub async fn git_stash(ctx: ToolContext, input: GitStashInput) -> Result<GitStas
                 args.push(r);
             }
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?
+                executor.execute_in_dir(p, &args).await?
             } else {
-                executor.execute(&args)?
+                executor.execute(&args).await?
             };
 
             Ok(GitStashOutput {
@@ -277,9 +314,9 @@ pub async fn git_stash(ctx: ToolContext, input: GitStashInput) -> Result<GitStas
         }
         Some("clear") => {
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &["stash", "clear"])?
+                executor.execute_in_dir(p, &["stash", "clear"]).await?
             } else {
-                executor.execute(&["stash", "clear"])?
+                executor.execute(&["stash", "clear"]).await?
             };
 
             Ok(GitStashOutput {
@@ -290,9 +327,13 @@ pub async fn git_stash(ctx: ToolContext, input: GitStashInput) -> Result<GitStas
         }
         Some("list") => {
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &["stash", "list", "--format=%gd|%gD|%s"])?
+                executor
+                    .execute_in_dir(p, &["stash", "list", "--format=%gd|%gD|%s"])
+                    .await?
             } else {
-                executor.execute(&["stash", "list", "--format=%gd|%gD|%s"])?
+                executor
+                    .execute(&["stash", "list", "--format=%gd|%gD|%s"])
+                    .await?
             };
 
             let stashes: Vec<GitStashEntry> = output
@@ -321,9 +362,9 @@ pub async fn git_stash(ctx: ToolContext, input: GitStashInput) -> Result<GitStas
         }
         _ => {
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &["stash", "list"])?
+                executor.execute_in_dir(p, &["stash", "list"]).await?
             } else {
-                executor.execute(&["stash", "list"])?
+                executor.execute(&["stash", "list"]).await?
             };
 
             Ok(GitStashOutput {
@@ -399,9 +440,9 @@ pub async fn git_reset(ctx: ToolContext, input: GitResetInput) -> Result<GitRese
     }
 
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args)?
+        executor.execute_in_dir(p, &args).await?
     } else {
-        executor.execute(&args)?
+        executor.execute(&args).await?
     };
 
     Ok(GitResetOutput {
@@ -467,9 +508,9 @@ pub async fn git_worktree(ctx: ToolContext, input: GitWorktreeInput) -> Result<G
             }
 
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?
+                executor.execute_in_dir(p, &args).await?
             } else {
-                executor.execute(&args)?
+                executor.execute(&args).await?
             };
 
             Ok(GitWorktreeOutput {
@@ -490,9 +531,9 @@ pub async fn git_worktree(ctx: ToolContext, input: GitWorktreeInput) -> Result<G
             }
 
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?
+                executor.execute_in_dir(p, &args).await?
             } else {
-                executor.execute(&args)?
+                executor.execute(&args).await?
             };
 
             Ok(GitWorktreeOutput {
@@ -503,9 +544,9 @@ pub async fn git_worktree(ctx: ToolContext, input: GitWorktreeInput) -> Result<G
         }
         Some("prune") => {
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &["worktree", "prune"])?
+                executor.execute_in_dir(p, &["worktree", "prune"]).await?
             } else {
-                executor.execute(&["worktree", "prune"])?
+                executor.execute(&["worktree", "prune"]).await?
             };
 
             Ok(GitWorktreeOutput {
@@ -516,9 +557,13 @@ pub async fn git_worktree(ctx: ToolContext, input: GitWorktreeInput) -> Result<G
         }
         _ => {
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &["worktree", "list", "--porcelain"])?
+                executor
+                    .execute_in_dir(p, &["worktree", "list", "--porcelain"])
+                    .await?
             } else {
-                executor.execute(&["worktree", "list", "--porcelain"])?
+                executor
+                    .execute(&["worktree", "list", "--porcelain"])
+                    .await?
             };
 
             let mut worktrees = Vec::new();
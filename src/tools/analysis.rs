@@ -97,6 +97,7 @@ pub async fn git_changelog_analyze(
 
     let current_branch = executor
         .execute_in_dir(&repo_path, &["rev-parse", "--abbrev-ref", "HEAD"])
+        .await
         .ok()
         .map(|o| o.stdout.trim().to_string())
         .filter(|s| !s.is_empty() && s != "HEAD");
@@ -122,7 +123,7 @@ pub async fn git_changelog_analyze(
     }
     let log_args_refs: Vec<&str> = log_args.iter().map(|s| s.as_str()).collect();
 
-    let log_output = executor.execute_in_dir(&repo_path, &log_args_refs)?;
+    let log_output = executor.execute_in_dir(&repo_path, &log_args_refs).await?;
     let commits: Vec<GitChangelogCommit> = log_output
         .stdout
         .lines()
@@ -154,14 +155,16 @@ pub async fn git_changelog_analyze(
         })
         .collect();
 
-    let tag_output = executor.execute_in_dir(
-        &repo_path,
-        &[
-            "for-each-ref",
-            "refs/tags",
-            "--format=%(refname:short)|%(objectname:short)|%(creatordate:unix)|%(subject)",
-        ],
-    )?;
+    let tag_output = executor
+        .execute_in_dir(
+            &repo_path,
+            &[
+                "for-each-ref",
+                "refs/tags",
+                "--format=%(refname:short)|%(objectname:short)|%(creatordate:unix)|%(subject)",
+            ],
+        )
+        .await?;
     let tags: Vec<GitChangelogTag> = tag_output
         .stdout
         .lines()
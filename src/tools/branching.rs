@@ -1,4 +1,5 @@
 use crate::error::Result;
+use crate::git::{ensure_signing_key, signing_error};
 use crate::tools::ToolContext;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -65,9 +66,9 @@ pub async fn git_branch(ctx: ToolContext, input: GitBranchInput) -> Result<GitBr
                 args.push(start);
             }
             if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?;
+                executor.execute_in_dir(p, &args).await?;
             } else {
-                executor.execute(&args)?;
+                executor.execute(&args).await?;
             }
             Ok(GitBranchOutput {
                 success: true,
@@ -85,9 +86,9 @@ pub async fn git_branch(ctx: ToolContext, input: GitBranchInput) -> Result<GitBr
                 args.push(name);
             }
             if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?;
+                executor.execute_in_dir(p, &args).await?;
             } else {
-                executor.execute(&args)?;
+                executor.execute(&args).await?;
             }
             Ok(GitBranchOutput {
                 success: true,
@@ -105,9 +106,9 @@ pub async fn git_branch(ctx: ToolContext, input: GitBranchInput) -> Result<GitBr
                 args.push(new_name);
             }
             if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?;
+                executor.execute_in_dir(p, &args).await?;
             } else {
-                executor.execute(&args)?;
+                executor.execute(&args).await?;
             }
             Ok(GitBranchOutput {
                 success: true,
@@ -118,9 +119,11 @@ pub async fn git_branch(ctx: ToolContext, input: GitBranchInput) -> Result<GitBr
         }
         Some("show-current") | None => {
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &["branch", "--show-current"])?
+                executor
+                    .execute_in_dir(p, &["branch", "--show-current"])
+                    .await?
             } else {
-                executor.execute(&["branch", "--show-current"])?
+                executor.execute(&["branch", "--show-current"]).await?
             };
             let current = output.stdout.trim().to_string();
             Ok(GitBranchOutput {
@@ -148,9 +151,9 @@ pub async fn git_branch(ctx: ToolContext, input: GitBranchInput) -> Result<GitBr
             }
 
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?
+                executor.execute_in_dir(p, &args).await?
             } else {
-                executor.execute(&args)?
+                executor.execute(&args).await?
             };
 
             let branches: Vec<GitBranch> = output
@@ -241,9 +244,9 @@ pub async fn git_checkout(ctx: ToolContext, input: GitCheckoutInput) -> Result<G
     }
 
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args)?
+        executor.execute_in_dir(p, &args).await?
     } else {
-        executor.execute(&args)?
+        executor.execute(&args).await?
     };
 
     Ok(GitCheckoutOutput {
@@ -293,9 +296,9 @@ pub async fn git_merge(ctx: ToolContext, input: GitMergeInput) -> Result<GitMerg
 
     if input.abort.unwrap_or(false) {
         if let Some(ref p) = path {
-            executor.execute_in_dir(p, &["merge", "--abort"])?;
+            executor.execute_in_dir(p, &["merge", "--abort"]).await?;
         } else {
-            executor.execute(&["merge", "--abort"])?;
+            executor.execute(&["merge", "--abort"]).await?;
         }
         return Ok(GitMergeOutput {
             success: true,
@@ -305,8 +308,16 @@ pub async fn git_merge(ctx: ToolContext, input: GitMergeInput) -> Result<GitMerg
         });
     }
 
+    if ctx.config.git_sign_commits {
+        ensure_signing_key(&executor, path.as_deref()).await?;
+    }
+
     let mut args = vec!["merge"];
 
+    if ctx.config.git_sign_commits {
+        args.push("-S");
+    }
+
     if input.no_fast_forward.unwrap_or(false) {
         args.push("--no-ff");
     }
@@ -328,10 +339,11 @@ pub async fn git_merge(ctx: ToolContext, input: GitMergeInput) -> Result<GitMerg
     args.push(&input.branch);
 
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args)?
+        executor.execute_in_dir(p, &args).await
     } else {
-        executor.execute(&args)?
-    };
+        executor.execute(&args).await
+    }
+    .map_err(signing_error)?;
 
     let combined = format!("{}\n{}", output.stdout, output.stderr).to_lowercase();
     let has_conflicts = combined.contains("conflict");
@@ -413,9 +425,9 @@ pub async fn git_rebase(ctx: ToolContext, input: GitRebaseInput) -> Result<GitRe
     }
 
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args)?
+        executor.execute_in_dir(p, &args).await?
     } else {
-        executor.execute(&args)?
+        executor.execute(&args).await?
     };
 
     Ok(GitRebaseOutput {
@@ -475,9 +487,9 @@ pub async fn git_cherry_pick(
     }
 
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args)?
+        executor.execute_in_dir(p, &args).await?
     } else {
-        executor.execute(&args)?
+        executor.execute(&args).await?
     };
 
     let combined = format!("{}\n{}", output.stdout, output.stderr).to_lowercase();
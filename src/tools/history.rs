@@ -1,4 +1,5 @@
-use crate::error::Result;
+use crate::error::{GitMcpError, Result};
+use crate::git::{verify_commits, verify_tag, SignatureVerification};
 use crate::tools::ToolContext;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -41,6 +42,9 @@ pub struct GitLogInput {
 
     #[schemars(description = "Show full diff")]
     pub patch: Option<bool>,
+
+    #[schemars(description = "Verify each commit's signature")]
+    pub signatures: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -51,6 +55,8 @@ pub struct GitCommit {
     pub email: String,
     pub date: String,
     pub message: String,
+    /// Set when signatures were requested.
+    pub signature: Option<SignatureVerification>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -124,12 +130,12 @@ pub async fn git_log(ctx: ToolContext, input: GitLogInput) -> Result<GitLogOutpu
 
     let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args_refs)?
+        executor.execute_in_dir(p, &args_refs).await?
     } else {
-        executor.execute(&args_refs)?
+        executor.execute(&args_refs).await?
     };
 
-    let commits: Vec<GitCommit> = output
+    let mut commits: Vec<GitCommit> = output
         .stdout
         .lines()
         .filter(|l| l.contains('|'))
@@ -143,6 +149,7 @@ pub async fn git_log(ctx: ToolContext, input: GitLogInput) -> Result<GitLogOutpu
                     email: parts[3].to_string(),
                     date: parts[4].to_string(),
                     message: parts[5].to_string(),
+                    signature: None,
                 })
             } else {
                 None
@@ -150,6 +157,14 @@ pub async fn git_log(ctx: ToolContext, input: GitLogInput) -> Result<GitLogOutpu
         })
         .collect();
 
+    if input.signatures.unwrap_or(false) {
+        let hashes: Vec<String> = commits.iter().map(|c| c.hash.clone()).collect();
+        let verified = verify_commits(&executor, path.as_deref(), &hashes).await?;
+        for (commit, (_, signature)) in commits.iter_mut().zip(verified) {
+            commit.signature = Some(signature);
+        }
+    }
+
     let total = Some(commits.len() as i32);
 
     Ok(GitLogOutput {
@@ -175,6 +190,9 @@ pub struct GitShowInput {
 
     #[schemars(description = "Specific file path")]
     pub file_path: Option<String>,
+
+    #[schemars(description = "Verify the signature of the commit or annotated tag")]
+    pub signatures: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -182,6 +200,9 @@ pub struct GitShowOutput {
     pub success: bool,
     pub content: String,
     pub commit: Option<GitCommit>,
+    /// Signature of the object shown: the tag itself for an annotated tag,
+    /// else the commit. Set when signatures were requested.
+    pub signature: Option<SignatureVerification>,
 }
 
 pub async fn git_show(ctx: ToolContext, input: GitShowInput) -> Result<GitShowOutput> {
@@ -212,9 +233,9 @@ pub async fn git_show(ctx: ToolContext, input: GitShowInput) -> Result<GitShowOu
 
     let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args_refs)?
+        executor.execute_in_dir(p, &args_refs).await?
     } else {
-        executor.execute(&args_refs)?
+        executor.execute(&args_refs).await?
     };
 
     let commit = output
@@ -232,16 +253,232 @@ pub async fn git_show(ctx: ToolContext, input: GitShowInput) -> Result<GitShowOu
                     email: parts[3].to_string(),
                     date: parts[4].to_string(),
                     message: parts[5].to_string(),
+                    signature: None,
                 })
             } else {
                 None
             }
         });
 
+    let signature = if input.signatures.unwrap_or(false) {
+        let object = input.object.as_deref().unwrap_or("HEAD");
+        let hash = resolve_object(&executor, path.as_deref(), object).await?;
+        Some(
+            match object_type(&executor, path.as_deref(), &hash)
+                .await?
+                .as_str()
+            {
+                "tag" => verify_tag(&executor, path.as_deref(), &hash).await,
+                _ => verify_commits(&executor, path.as_deref(), &[hash])
+                    .await?
+                    .into_iter()
+                    .map(|(_, signature)| signature)
+                    .next()
+                    .ok_or_else(|| {
+                        GitMcpError::InvalidInput(format!("{} is not a commit or tag", object))
+                    })?,
+            },
+        )
+    } else {
+        None
+    };
+
     Ok(GitShowOutput {
         success: true,
         content: output.stdout,
         commit,
+        signature,
+    })
+}
+
+async fn object_type(
+    executor: &crate::git::GitExecutor,
+    dir: Option<&std::path::Path>,
+    object: &str,
+) -> Result<String> {
+    let args = ["cat-file", "-t", object];
+    let output = match dir {
+        Some(dir) => executor.execute_in_dir(dir, &args).await,
+        None => executor.execute(&args).await,
+    }
+    .map_err(|_| GitMcpError::InvalidInput(format!("Unknown object: {}", object)))?;
+    Ok(output.stdout.trim().to_string())
+}
+
+/// Splits `A..B` or `A...B` into its ends, an empty end meaning `HEAD` as in
+/// git, and whether the range is symmetric.
+fn split_range(object: &str) -> Option<(&str, &str, bool)> {
+    let (from, to, symmetric) = match object.split_once("...") {
+        Some((from, to)) => (from, to, true),
+        None => {
+            let (from, to) = object.split_once("..")?;
+            (from, to, false)
+        }
+    };
+    Some((
+        if from.is_empty() { "HEAD" } else { from },
+        if to.is_empty() { "HEAD" } else { to },
+        symmetric,
+    ))
+}
+
+/// Resolves a revision given by the client to an object hash, so that
+/// nothing it names reaches another git command where it could be read as
+/// an option.
+async fn resolve_object(
+    executor: &crate::git::GitExecutor,
+    dir: Option<&std::path::Path>,
+    object: &str,
+) -> Result<String> {
+    if object.starts_with('-') {
+        return Err(GitMcpError::InvalidInput(format!(
+            "Invalid object: {}",
+            object
+        )));
+    }
+    let args = [
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        "--end-of-options",
+        object,
+    ];
+    let output = match dir {
+        Some(dir) => executor.execute_in_dir(dir, &args).await,
+        None => executor.execute(&args).await,
+    }
+    .map_err(|_| GitMcpError::InvalidInput(format!("Unknown object: {}", object)))?;
+    Ok(output.stdout.trim().to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GitVerifyInput {
+    #[schemars(description = "Path to the repository")]
+    pub path: Option<String>,
+
+    #[schemars(description = "Commits, tags or commit ranges (A..B) to verify; defaults to HEAD")]
+    pub objects: Option<Vec<String>>,
+
+    #[schemars(description = "Maximum number of commits to verify per range")]
+    pub max_count: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GitVerifyResult {
+    /// The commit, tag or range as requested.
+    pub object: String,
+    /// `commit` or `tag`.
+    pub kind: String,
+    pub hash: String,
+    pub signature: SignatureVerification,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GitVerifyOutput {
+    pub success: bool,
+    /// Whether anything was checked and every signature is good.
+    pub all_good: bool,
+    pub results: Vec<GitVerifyResult>,
+}
+
+pub async fn git_verify(ctx: ToolContext, input: GitVerifyInput) -> Result<GitVerifyOutput> {
+    let executor = ctx.executor.read().await;
+
+    let path = input.path.as_ref().map(PathBuf::from);
+    let dir = path.as_deref();
+    let run = |args: Vec<String>| {
+        let executor = &executor;
+        async move {
+            let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+            match dir {
+                Some(dir) => executor.execute_in_dir(dir, &args).await,
+                None => executor.execute(&args).await,
+            }
+        }
+    };
+
+    let objects = input
+        .objects
+        .clone()
+        .filter(|o| !o.is_empty())
+        .unwrap_or_else(|| vec!["HEAD".to_string()]);
+    let mut results = Vec::new();
+
+    for object in objects {
+        if let Some((from, to, symmetric)) = split_range(&object) {
+            let from = resolve_object(&executor, dir, from).await?;
+            let to = resolve_object(&executor, dir, to).await?;
+            let mut args = vec!["rev-list".to_string()];
+            if let Some(n) = input.max_count {
+                args.push(format!("--max-count={}", n));
+            }
+            args.push("--end-of-options".to_string());
+            args.push(format!(
+                "{}{}{}",
+                from,
+                if symmetric { "..." } else { ".." },
+                to
+            ));
+            let hashes: Vec<String> = run(args)
+                .await?
+                .stdout
+                .lines()
+                .map(|l| l.to_string())
+                .collect();
+            for (hash, signature) in verify_commits(&executor, dir, &hashes).await? {
+                results.push(GitVerifyResult {
+                    object: object.clone(),
+                    kind: "commit".to_string(),
+                    hash,
+                    signature,
+                });
+            }
+            continue;
+        }
+
+        let hash = resolve_object(&executor, dir, &object).await?;
+        let kind = object_type(&executor, dir, &hash).await?;
+        // A lightweight tag names a commit but is still checked as a tag, so
+        // release gates see it as unsigned.
+        let is_tag = kind == "tag"
+            || run(vec![
+                "rev-parse".to_string(),
+                "--verify".to_string(),
+                "--quiet".to_string(),
+                format!("refs/tags/{}", object),
+            ])
+            .await
+            .is_ok();
+
+        let (kind, signature) = match kind.as_str() {
+            _ if is_tag => ("tag", verify_tag(&executor, dir, &hash).await),
+            "commit" => {
+                let verified = verify_commits(&executor, dir, std::slice::from_ref(&hash)).await?;
+                match verified.into_iter().next() {
+                    Some((_, signature)) => ("commit", signature),
+                    None => continue,
+                }
+            }
+            other => {
+                return Err(GitMcpError::InvalidInput(format!(
+                    "{} is a {}, not a commit or tag",
+                    object, other
+                )))
+            }
+        };
+        results.push(GitVerifyResult {
+            object,
+            kind: kind.to_string(),
+            hash,
+            signature,
+        });
+    }
+
+    Ok(GitVerifyOutput {
+        success: true,
+        // An empty range proves nothing, so it is not reported as all good.
+        all_good: !results.is_empty() && results.iter().all(|r| r.signature.is_good()),
+        results,
     })
 }
 
@@ -298,9 +535,9 @@ pub async fn git_blame(ctx: ToolContext, input: GitBlameInput) -> Result<GitBlam
 
     let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args_refs)?
+        executor.execute_in_dir(p, &args_refs).await?
     } else {
-        executor.execute(&args_refs)?
+        executor.execute(&args_refs).await?
     };
 
     let mut lines = Vec::new();
@@ -392,9 +629,9 @@ pub async fn git_reflog(ctx: ToolContext, input: GitReflogInput) -> Result<GitRe
 
     let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args_refs)?
+        executor.execute_in_dir(p, &args_refs).await?
     } else {
-        executor.execute(&args_refs)?
+        executor.execute(&args_refs).await?
     };
 
     let entries: Vec<GitReflogEntry> = output
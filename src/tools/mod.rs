@@ -6,15 +6,26 @@ pub mod remote;
 pub mod repo;
 pub mod staging;
 
+use crate::auth::{AuthIdentity, Scope};
 use crate::config::Config;
-use crate::git::GitExecutor;
+use crate::git::{GitExecutor, ProgressSink};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
+
+/// Channel for server-initiated JSON-RPC messages (already serialized) to the
+/// client that issued the current request.
+pub type Notifier = mpsc::UnboundedSender<String>;
 
 #[derive(Clone)]
 pub struct ToolContext {
     pub config: Arc<Config>,
     pub executor: Arc<RwLock<GitExecutor>>,
+    pub notifier: Option<Notifier>,
+    pub progress: Option<ProgressSink>,
+    /// The authenticated HTTP caller; `None` when authentication is off.
+    pub identity: Option<Arc<AuthIdentity>>,
+    /// The HTTP session the context belongs to, if any.
+    pub session_id: Option<String>,
 }
 
 impl ToolContext {
@@ -24,6 +35,10 @@ impl ToolContext {
         Self {
             config,
             executor: Arc::new(RwLock::new(executor)),
+            notifier: None,
+            progress: None,
+            identity: None,
+            session_id: None,
         }
     }
 
@@ -32,6 +47,133 @@ impl ToolContext {
         Self {
             config,
             executor: Arc::new(RwLock::new(executor)),
+            notifier: None,
+            progress: None,
+            identity: None,
+            session_id: None,
         }
     }
+
+    /// Switches the context to `config`, e.g. after a reload. The executor
+    /// shared with the session follows it for calls made through
+    /// `execute_tool`.
+    pub fn with_config(mut self, config: Arc<Config>) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_notifier(mut self, notifier: Notifier) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    pub fn with_progress(mut self, progress: ProgressSink) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn with_session_id(mut self, session_id: String) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    pub fn with_identity(mut self, identity: AuthIdentity) -> Self {
+        self.identity = Some(Arc::new(identity));
+        self
+    }
+}
+
+/// Tools that never change repository state.
+const READ_ONLY_TOOLS: &[&str] = &[
+    "git_status",
+    "git_diff",
+    "git_log",
+    "git_show",
+    "git_blame",
+    "git_reflog",
+    "git_verify",
+    "git_changelog_analyze",
+    "git_wrapup_instructions",
+    "git_set_working_dir",
+    "git_clear_working_dir",
+];
+
+/// Tools whose mode argument decides whether they change repository state.
+const MIXED_TOOLS: &[&str] = &[
+    "git_branch",
+    "git_tag",
+    "git_stash",
+    "git_remote",
+    "git_worktree",
+];
+
+/// Whether a tool stays listed when the server is read-only: it is either
+/// read-only or has read-only modes.
+pub fn available_in_read_only(tool_name: &str) -> bool {
+    READ_ONLY_TOOLS.contains(&tool_name) || MIXED_TOOLS.contains(&tool_name)
+}
+
+/// Whether a `tools/call` with these arguments can change repository state
+/// (refs, index, working tree, config or stashes). Mode-dependent tools are
+/// classified by the sub-operation they would run.
+pub fn mutates_repository(tool_name: &str, arguments: &serde_json::Value) -> bool {
+    let arg_str = |key: &str| arguments.get(key).and_then(|v| v.as_str());
+    let arg_bool = |key: &str| arguments.get(key).and_then(|v| v.as_bool());
+
+    match tool_name {
+        name if READ_ONLY_TOOLS.contains(&name) => false,
+        "git_branch" => matches!(arg_str("operation"), Some("create" | "delete" | "rename")),
+        "git_tag" => matches!(arg_str("mode"), Some("create" | "delete")),
+        "git_stash" => matches!(
+            arg_str("mode"),
+            None | Some("push" | "pop" | "apply" | "drop" | "clear")
+        ),
+        "git_remote" => matches!(
+            arg_str("mode"),
+            Some("add" | "remove" | "rename" | "set-url")
+        ),
+        "git_worktree" => matches!(arg_str("mode"), Some("add" | "remove" | "prune")),
+        "git_clean" | "git_push" => !arg_bool("dry_run").unwrap_or(false),
+        _ => true,
+    }
+}
+
+/// The token scope a `tools/call` with these arguments needs. Destructive
+/// operations need `git:admin` on top of the scope their tool normally needs.
+pub fn required_scope(tool_name: &str, arguments: &serde_json::Value) -> Scope {
+    let arg_str = |key: &str| arguments.get(key).and_then(|v| v.as_str());
+    let arg_bool = |key: &str| {
+        arguments
+            .get(key)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    };
+
+    let destructive = match tool_name {
+        "git_reset" => arg_str("mode") == Some("hard"),
+        "git_clean" => !arg_bool("dry_run"),
+        "git_push" => {
+            arg_bool("force")
+                || arg_bool("force_with_lease")
+                || arg_bool("delete")
+                || arg_str("branch").is_some_and(|b| b.starts_with('+') || b.starts_with(':'))
+        }
+        "git_branch" => arg_str("operation") == Some("delete") && arg_bool("force"),
+        "git_checkout" => arg_bool("force"),
+        "git_stash" => arg_str("mode") == Some("clear"),
+        "git_worktree" => arg_str("mode") == Some("remove") && arg_bool("force"),
+        // Overriding the secret scan is as consequential as a force push.
+        "git_commit" => arg_bool("allow_secrets"),
+        _ => false,
+    };
+
+    if destructive {
+        Scope::Admin
+    } else if tool_name == "git_push" && !arg_bool("dry_run") {
+        Scope::Push
+    } else if mutates_repository(tool_name, arguments) {
+        Scope::Write
+    } else {
+        Scope::Read
+    }
 }
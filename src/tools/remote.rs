@@ -59,9 +59,9 @@ pub async fn git_remote(ctx: ToolContext, input: GitRemoteInput) -> Result<GitRe
                 args.push(push);
             }
             if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?;
+                executor.execute_in_dir(p, &args).await?;
             } else {
-                executor.execute(&args)?;
+                executor.execute(&args).await?;
             }
             Ok(GitRemoteOutput {
                 success: true,
@@ -75,9 +75,9 @@ pub async fn git_remote(ctx: ToolContext, input: GitRemoteInput) -> Result<GitRe
                 args.push(name);
             }
             if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?;
+                executor.execute_in_dir(p, &args).await?;
             } else {
-                executor.execute(&args)?;
+                executor.execute(&args).await?;
             }
             Ok(GitRemoteOutput {
                 success: true,
@@ -94,9 +94,9 @@ pub async fn git_remote(ctx: ToolContext, input: GitRemoteInput) -> Result<GitRe
                 args.push(new_name);
             }
             if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?;
+                executor.execute_in_dir(p, &args).await?;
             } else {
-                executor.execute(&args)?;
+                executor.execute(&args).await?;
             }
             Ok(GitRemoteOutput {
                 success: true,
@@ -117,9 +117,9 @@ pub async fn git_remote(ctx: ToolContext, input: GitRemoteInput) -> Result<GitRe
                 args.push(name);
             }
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?
+                executor.execute_in_dir(p, &args).await?
             } else {
-                executor.execute(&args)?
+                executor.execute(&args).await?
             };
             Ok(GitRemoteOutput {
                 success: true,
@@ -140,9 +140,9 @@ pub async fn git_remote(ctx: ToolContext, input: GitRemoteInput) -> Result<GitRe
                 args.push(url);
             }
             if let Some(ref p) = path {
-                executor.execute_in_dir(p, &args)?;
+                executor.execute_in_dir(p, &args).await?;
             } else {
-                executor.execute(&args)?;
+                executor.execute(&args).await?;
             }
             Ok(GitRemoteOutput {
                 success: true,
@@ -152,9 +152,9 @@ pub async fn git_remote(ctx: ToolContext, input: GitRemoteInput) -> Result<GitRe
         }
         _ => {
             let output = if let Some(ref p) = path {
-                executor.execute_in_dir(p, &["remote", "-v"])?
+                executor.execute_in_dir(p, &["remote", "-v"]).await?
             } else {
-                executor.execute(&["remote", "-v"])?
+                executor.execute(&["remote", "-v"]).await?
             };
 
             let mut by_name: HashMap<String, (Option<String>, Option<String>)> = HashMap::new();
@@ -232,6 +232,10 @@ pub async fn git_fetch(ctx: ToolContext, input: GitFetchInput) -> Result<GitFetc
 
     let mut args: Vec<String> = vec!["fetch".into()];
 
+    if ctx.progress.is_some() {
+        args.push("--progress".into());
+    }
+
     if input.prune.unwrap_or(false) {
         args.push("--prune".into());
     }
@@ -249,11 +253,9 @@ pub async fn git_fetch(ctx: ToolContext, input: GitFetchInput) -> Result<GitFetc
     args.push(remote.clone());
 
     let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-    let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args_refs)?
-    } else {
-        executor.execute(&args_refs)?
-    };
+    let output = executor
+        .execute_with_progress(path.as_deref(), &args_refs, ctx.progress.as_ref())
+        .await?;
 
     let fetched_refs: Vec<String> = output
         .stdout
@@ -303,6 +305,10 @@ pub async fn git_pull(ctx: ToolContext, input: GitPullInput) -> Result<GitPullOu
 
     let mut args = vec!["pull"];
 
+    if ctx.progress.is_some() {
+        args.push("--progress");
+    }
+
     if input.rebase.unwrap_or(false) {
         args.push("--rebase");
     }
@@ -319,11 +325,9 @@ pub async fn git_pull(ctx: ToolContext, input: GitPullInput) -> Result<GitPullOu
         args.push(branch);
     }
 
-    let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args)?
-    } else {
-        executor.execute(&args)?
-    };
+    let output = executor
+        .execute_with_progress(path.as_deref(), &args, ctx.progress.as_ref())
+        .await?;
 
     let fast_forwarded = output.stdout.contains("Fast-forward");
 
@@ -380,6 +384,10 @@ pub async fn git_push(ctx: ToolContext, input: GitPushInput) -> Result<GitPushOu
 
     let mut args = vec!["push"];
 
+    if ctx.progress.is_some() {
+        args.push("--progress");
+    }
+
     if input.force.unwrap_or(false) {
         args.push("--force");
     }
@@ -411,11 +419,9 @@ pub async fn git_push(ctx: ToolContext, input: GitPushInput) -> Result<GitPushOu
         args.push(branch);
     }
 
-    let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args)?
-    } else {
-        executor.execute(&args)?
-    };
+    let output = executor
+        .execute_with_progress(path.as_deref(), &args, ctx.progress.as_ref())
+        .await?;
 
     Ok(GitPushOutput {
         success: true,
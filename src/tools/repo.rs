@@ -36,9 +36,9 @@ pub async fn git_status(ctx: ToolContext, input: GitStatusInput) -> Result<GitSt
     }
 
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args)?
+        executor.execute_in_dir(p, &args).await?
     } else {
-        executor.execute(&args)?
+        executor.execute(&args).await?
     };
     let stdout = output.stdout.trim();
 
@@ -167,7 +167,7 @@ pub async fn git_init(ctx: ToolContext, input: GitInitInput) -> Result<GitInitOu
 
     args.push(&input.path);
 
-    executor.execute(&args)?;
+    executor.execute(&args).await?;
 
     Ok(GitInitOutput {
         success: true,
@@ -206,6 +206,10 @@ pub async fn git_clone(ctx: ToolContext, input: GitCloneInput) -> Result<GitClon
 
     let mut args: Vec<String> = vec!["clone".into()];
 
+    if ctx.progress.is_some() {
+        args.push("--progress".into());
+    }
+
     if let Some(branch) = &input.branch {
         args.push("--branch".into());
         args.push(branch.clone());
@@ -227,7 +231,9 @@ pub async fn git_clone(ctx: ToolContext, input: GitCloneInput) -> Result<GitClon
     }
 
     let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-    executor.execute(&args_refs)?;
+    executor
+        .execute_with_progress(None, &args_refs, ctx.progress.as_ref())
+        .await?;
 
     let path = input.local_path.clone().unwrap_or_else(|| {
         let url = &input.url;
@@ -297,9 +303,9 @@ pub async fn git_clean(ctx: ToolContext, input: GitCleanInput) -> Result<GitClea
     }
 
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args)?
+        executor.execute_in_dir(p, &args).await?
     } else {
-        executor.execute(&args)?
+        executor.execute(&args).await?
     };
 
     let cleaned_files: Vec<String> = output
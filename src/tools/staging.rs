@@ -1,4 +1,6 @@
-use crate::error::Result;
+use crate::error::{GitMcpError, Result};
+use crate::git::{ensure_signing_key, object_signature, signing_error, SignatureInfo};
+use crate::redact::scan_diff;
 use crate::tools::ToolContext;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -53,9 +55,9 @@ pub async fn git_add(ctx: ToolContext, input: GitAddInput) -> Result<GitAddOutpu
     }
 
     if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args)?;
+        executor.execute_in_dir(p, &args).await?;
     } else {
-        executor.execute(&args)?;
+        executor.execute(&args).await?;
     }
 
     Ok(GitAddOutput {
@@ -87,6 +89,11 @@ pub struct GitCommitInput {
 
     #[schemars(description = "Skip pre-commit hooks")]
     pub no_verify: Option<bool>,
+
+    #[schemars(
+        description = "Commit even though the secret scan found likely credentials in the staged changes"
+    )]
+    pub allow_secrets: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -94,6 +101,7 @@ pub struct GitCommitOutput {
     pub success: bool,
     pub commit_hash: Option<String>,
     pub branch: Option<String>,
+    pub signature: SignatureInfo,
     pub message: String,
 }
 
@@ -109,14 +117,56 @@ pub async fn git_commit(ctx: ToolContext, input: GitCommitInput) -> Result<GitCo
             add_args.push(file);
         }
         if let Some(ref p) = path {
-            executor.execute_in_dir(p, &add_args)?;
+            executor.execute_in_dir(p, &add_args).await?;
+        } else {
+            executor.execute(&add_args).await?;
+        }
+    }
+
+    if ctx.config.secret_scan && !input.allow_secrets.unwrap_or(false) {
+        // Fixed prefixes and unquoted names whatever the repository's diff
+        // settings, so every file is attributed and scanned.
+        let diff_args = [
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "--cached",
+            "--no-color",
+            "--no-ext-diff",
+            "-U0",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+        ];
+        let diff = if let Some(ref p) = path {
+            executor.execute_in_dir(p, &diff_args).await?
         } else {
-            executor.execute(&add_args)?;
+            executor.execute(&diff_args).await?
+        };
+        let findings = scan_diff(&diff.stdout);
+        if !findings.is_empty() {
+            let report: Vec<String> = findings.iter().map(|f| format!("  {}", f)).collect();
+            return Err(GitMcpError::PolicyViolation {
+                rule: "secret_scan".to_string(),
+                message: format!(
+                    "the staged changes look like they contain credentials:\n{}\n\
+                     Nothing was committed and the changes are still staged. Unstage the \
+                     secrets, or pass allow_secrets if these are not real credentials.",
+                    report.join("\n")
+                ),
+            });
         }
     }
 
+    if ctx.config.git_sign_commits {
+        ensure_signing_key(&executor, path.as_deref()).await?;
+    }
+
     let mut args = vec!["commit", "-m", &input.message];
 
+    if ctx.config.git_sign_commits {
+        args.push("-S");
+    }
+
     if let Some(author) = &input.author {
         args.push("--author");
         args.push(author);
@@ -135,10 +185,11 @@ pub async fn git_commit(ctx: ToolContext, input: GitCommitInput) -> Result<GitCo
     }
 
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args)?
+        executor.execute_in_dir(p, &args).await
     } else {
-        executor.execute(&args)?
-    };
+        executor.execute(&args).await
+    }
+    .map_err(signing_error)?;
 
     // Extract commit hash from output
     let commit_hash = output
@@ -153,9 +204,11 @@ pub async fn git_commit(ctx: ToolContext, input: GitCommitInput) -> Result<GitCo
 
     // Get current branch
     let branch_output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &["branch", "--show-current"])?
+        executor
+            .execute_in_dir(p, &["branch", "--show-current"])
+            .await?
     } else {
-        executor.execute(&["branch", "--show-current"])?
+        executor.execute(&["branch", "--show-current"]).await?
     };
     let branch = branch_output.stdout.trim().to_string();
     let branch = if branch.is_empty() {
@@ -164,10 +217,13 @@ pub async fn git_commit(ctx: ToolContext, input: GitCommitInput) -> Result<GitCo
         Some(branch)
     };
 
+    let signature = object_signature(&executor, path.as_deref(), "commit", "HEAD").await;
+
     Ok(GitCommitOutput {
         success: true,
         commit_hash,
         branch,
+        signature,
         message: format!("Created commit: {}", input.message),
     })
 }
@@ -244,9 +300,9 @@ pub async fn git_diff(ctx: ToolContext, input: GitDiffInput) -> Result<GitDiffOu
     }
 
     let output = if let Some(ref p) = path {
-        executor.execute_in_dir(p, &args)?
+        executor.execute_in_dir(p, &args).await?
     } else {
-        executor.execute(&args)?
+        executor.execute(&args).await?
     };
 
     // Parse stat if available